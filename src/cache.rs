@@ -0,0 +1,345 @@
+//! Hydrogen // Cache
+//!
+//! Cache for the tracks loaded from Lavalink, avoiding resolving the same URL repeatedly, and for the users who requested them.
+
+use std::{
+    collections::VecDeque,
+    future::Future,
+    hash::Hash,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use serenity::all::UserId;
+
+use crate::{
+    lavalink::rest::{LavalinkLoadResultType, LavalinkTrackLoading},
+    roll::Params,
+};
+
+/// A result stored in the cache.
+struct CachedTrackLoading {
+    /// When the result was loaded from Lavalink.
+    loaded_at: Instant,
+
+    /// When the result was used for the last time.
+    used_at: Instant,
+
+    /// The result loaded from Lavalink.
+    result: LavalinkTrackLoading,
+}
+
+/// Least recently used cache for the results of the track loading.
+pub struct TrackCache {
+    /// The cached results, keyed by the query.
+    entries: DashMap<String, CachedTrackLoading>,
+
+    /// Maximum amount of results stored, `0` disables the cache.
+    capacity: usize,
+
+    /// How long a result is valid.
+    ttl: Duration,
+}
+
+impl TrackCache {
+    /// Creates a new cache with the given capacity and time-to-live.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: DashMap::new(),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Gets a result from the cache if it's still valid.
+    pub fn get(&self, query: &str) -> Option<LavalinkTrackLoading> {
+        let key = normalize_query(query);
+
+        let mut entry = self.entries.get_mut(key)?;
+        if entry.loaded_at.elapsed() >= self.ttl {
+            drop(entry);
+            self.entries.remove(key);
+            return None;
+        }
+
+        entry.used_at = Instant::now();
+        Some(entry.result.clone())
+    }
+
+    /// Stores a result in the cache.
+    ///
+    /// Only loaded tracks and playlists are stored, as search results can change over time.
+    pub fn insert(&self, query: &str, result: &LavalinkTrackLoading) {
+        if self.capacity == 0
+            || !matches!(
+                result.load_type,
+                LavalinkLoadResultType::TrackLoaded | LavalinkLoadResultType::PlaylistLoaded
+            )
+        {
+            return;
+        }
+
+        // Remove the expired results before checking the capacity.
+        self.entries
+            .retain(|_, entry| entry.loaded_at.elapsed() < self.ttl);
+
+        // Evict the least recently used result.
+        if self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|entry| entry.used_at)
+                .map(|entry| entry.key().clone());
+
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+
+        let now = Instant::now();
+        self.entries.insert(
+            normalize_query(query).to_owned(),
+            CachedTrackLoading {
+                loaded_at: now,
+                used_at: now,
+                result: result.clone(),
+            },
+        );
+    }
+}
+
+/// Normalizes the query used as the cache key.
+fn normalize_query(query: &str) -> &str {
+    query.trim()
+}
+
+/// Name and avatar of a user, shown in the player messages.
+#[derive(Clone, Debug)]
+pub struct CachedUser {
+    /// The user's name.
+    pub name: String,
+
+    /// The user's avatar URL.
+    pub avatar_url: Option<String>,
+}
+
+/// Cache for the users who requested the tracks, avoiding fetching them on every player message update.
+pub type UserCache = TtlCache<UserId, CachedUser>;
+
+/// Cache for the last roll of each user, allowing it to be rolled again.
+pub type RollCache = TtlCache<UserId, Params>;
+
+/// Map whose entries expire after a time-to-live, optionally bounded, evicting the oldest entries first.
+///
+/// The insertion order is kept in a queue, so each insertion only removes the expired or evicted entries from its
+/// front, instead of scanning the whole map.
+pub struct TtlCache<K, V> {
+    /// The cached values, with the ID of their insertion and when it happened.
+    entries: DashMap<K, (u64, Instant, V)>,
+
+    /// The insertions in order and the ID of the next one.
+    order: Mutex<(VecDeque<Insertion<K>>, u64)>,
+
+    /// Maximum amount of values stored.
+    capacity: usize,
+
+    /// How long a value is valid.
+    ttl: Duration,
+}
+
+/// An insertion in a [`TtlCache`], identified by an ID, as the key can be inserted again later.
+struct Insertion<K> {
+    id: u64,
+    inserted_at: Instant,
+    key: K,
+}
+
+impl<K: Eq + Hash + Clone, V: Clone> TtlCache<K, V> {
+    /// Creates a new cache with the given time-to-live and no capacity limit.
+    pub fn new(ttl: Duration) -> Self {
+        Self::bounded(usize::MAX, ttl)
+    }
+
+    /// Creates a new cache with the given capacity and time-to-live.
+    pub fn bounded(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: DashMap::new(),
+            order: Mutex::new((VecDeque::new(), 0)),
+            capacity,
+            ttl,
+        }
+    }
+
+    /// Gets a value from the cache if it's still valid.
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.entries
+            .get(key)
+            .filter(|entry| entry.1.elapsed() < self.ttl)
+            .map(|entry| entry.2.clone())
+    }
+
+    /// Stores a value in the cache, replacing the previous one.
+    pub fn insert(&self, key: K, value: V) {
+        let mut order = self.order.lock().unwrap_or_else(|e| e.into_inner());
+        let (insertions, next_id) = &mut *order;
+
+        let id = *next_id;
+        *next_id += 1;
+
+        let now = Instant::now();
+        self.entries.insert(key.clone(), (id, now, value));
+        insertions.push_back(Insertion {
+            id,
+            inserted_at: now,
+            key,
+        });
+
+        // Remove from the oldest insertions until they're valid and the capacity is respected.
+        while let Some(oldest) = insertions.front() {
+            if oldest.inserted_at.elapsed() < self.ttl && self.entries.len() <= self.capacity {
+                break;
+            }
+
+            if let Some(oldest) = insertions.pop_front() {
+                // A replaced value was inserted again later, so it's kept.
+                self.entries
+                    .remove_if(&oldest.key, |_, entry| entry.0 == oldest.id);
+            }
+        }
+    }
+
+    /// Gets a value from the cache, or stores the one returned by `fetch`, which isn't stored when it's `None`.
+    pub async fn get_or_insert_with<F, Fut>(&self, key: K, fetch: F) -> Option<V>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Option<V>>,
+    {
+        if let Some(value) = self.get(&key) {
+            return Some(value);
+        }
+
+        let value = fetch().await?;
+        self.insert(key, value.clone());
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpListener,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+        thread::{sleep, spawn},
+    };
+
+    use reqwest::Client;
+
+    use super::*;
+
+    fn user() -> CachedUser {
+        CachedUser {
+            name: "deer".to_owned(),
+            avatar_url: None,
+        }
+    }
+
+    #[test]
+    fn user_cache_hit() {
+        let cache = UserCache::new(Duration::from_secs(60));
+        let user_id = UserId::new(1);
+
+        assert!(cache.get(&user_id).is_none());
+
+        cache.insert(user_id, user());
+        assert_eq!(cache.get(&user_id).map(|v| v.name), Some("deer".to_owned()));
+    }
+
+    #[test]
+    fn user_cache_expired() {
+        let cache = UserCache::new(Duration::ZERO);
+        let user_id = UserId::new(1);
+
+        cache.insert(user_id, user());
+        assert!(cache.get(&user_id).is_none());
+    }
+
+    #[test]
+    fn ttl_cache_expires_only_the_old_insertions() {
+        let cache = TtlCache::new(Duration::from_millis(50));
+
+        cache.insert(1, "first");
+        sleep(Duration::from_millis(60));
+        cache.insert(2, "second");
+
+        assert!(cache.get(&1).is_none());
+        assert_eq!(cache.get(&2), Some("second"));
+        assert_eq!(cache.entries.len(), 1);
+    }
+
+    #[test]
+    fn ttl_cache_keeps_replaced_values() {
+        let cache = TtlCache::bounded(2, Duration::from_secs(60));
+
+        cache.insert(1, "first");
+        cache.insert(2, "second");
+        cache.insert(1, "replaced");
+        cache.insert(3, "third");
+
+        assert_eq!(cache.get(&1), Some("replaced"));
+        assert!(cache.get(&2).is_none());
+        assert_eq!(cache.get(&3), Some("third"));
+    }
+
+    #[tokio::test]
+    async fn user_cache_avoids_fetching_again() {
+        // Respond to every request with the user's name, counting the requests.
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/users/1", listener.local_addr().unwrap());
+        let requests = Arc::new(AtomicUsize::new(0));
+        let requests_clone = requests.clone();
+        spawn(move || {
+            for mut stream in listener.incoming().flatten() {
+                requests_clone.fetch_add(1, Ordering::Relaxed);
+                let mut buffer = [0; 1024];
+                let _ = stream.read(&mut buffer);
+                let _ = stream.write_all(
+                    b"HTTP/1.1 200 OK\r\nContent-Length: 4\r\nConnection: close\r\n\r\ndeer",
+                );
+            }
+        });
+
+        let cache = UserCache::new(Duration::from_secs(60));
+        let client = Client::new();
+        for _ in 0..5 {
+            let user = cache
+                .get_or_insert_with(UserId::new(1), || async {
+                    let name = client.get(&url).send().await.ok()?.text().await.ok()?;
+                    Some(CachedUser {
+                        name,
+                        avatar_url: None,
+                    })
+                })
+                .await;
+
+            assert_eq!(user.map(|v| v.name), Some("deer".to_owned()));
+        }
+
+        assert_eq!(requests.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn roll_cache_evicts_oldest() {
+        let cache = RollCache::bounded(1, Duration::from_secs(60));
+
+        cache.insert(UserId::new(1), Params::default());
+        cache.insert(UserId::new(2), Params::default());
+
+        assert!(cache.get(&UserId::new(1)).is_none());
+        assert!(cache.get(&UserId::new(2)).is_some());
+    }
+}
@@ -7,6 +7,7 @@ use serenity::{all::CommandInteraction, builder::CreateCommand, client::Context}
 
 use crate::{
     handler::{Response, Result},
+    utils::translate_command,
     HydrogenContext, ShardManagerRunners, HYDROGEN_BUG_URL, HYDROGEN_NAME, HYDROGEN_REPOSITORY_URL,
     HYDROGEN_VERSION,
 };
@@ -135,14 +136,12 @@ pub async fn execute(
 ///
 /// If `i18n` is `None`, the translation will be ignored.
 pub fn register(i18n: Option<&I18n>) -> CreateCommand {
-    let mut command = CreateCommand::new("about");
-
-    if let Some(i18n) = i18n {
-        command = i18n.serenity_command_name("about", "name", command);
-        command = i18n.serenity_command_description("about", "description", command);
-    }
-
-    command
-        .description("Shows information about the bot.")
-        .dm_permission(true)
+    translate_command(
+        i18n,
+        "about",
+        "name",
+        "description",
+        CreateCommand::new("about"),
+    )
+    .description("Shows information about the bot.")
 }
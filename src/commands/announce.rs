@@ -0,0 +1,113 @@
+//! Hydrogen // Commands // Announce
+//!
+//! '/announce' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{
+    all::{CommandInteraction, CommandOptionType, Permissions},
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+};
+use tracing::error;
+
+use crate::{
+    handler::{Response, Result},
+    utils::{translate_command, translate_option, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL,
+};
+
+/// Executes the `/announce` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "announce", "embed_title");
+
+    // Get the enabled option value.
+    let Some(enabled) = interaction
+        .data
+        .options
+        .first()
+        .and_then(|v| v.value.as_bool())
+    else {
+        error!("cannot get the 'enabled' option");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Change the guild's setting.
+    data.manager.set_announce(data.guild_id, enabled).await;
+
+    if enabled {
+        Ok(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "announce", "enabled"),
+        })
+    } else {
+        // Get controls command's mention.
+        let controls_command = match hydrogen.commands_id.read().await.get("controls") {
+            Some(v) => format!("</controls:{}>", v.get()),
+            None => "`/controls`".to_owned(),
+        };
+
+        Ok(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "announce", "disabled")
+                .replace("{controls}", &controls_command),
+        })
+    }
+}
+
+/// Registers the `/announce` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "announce",
+        "name",
+        "description",
+        CreateCommand::new("announce"),
+    )
+    .description("Enable or disable the now playing message in this server.")
+    .add_option(translate_option(
+        i18n,
+        "announce",
+        "enabled_name",
+        "enabled_description",
+        CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "enabled",
+            "Whether the now playing message should be sent.",
+        )
+        .required(true),
+    ))
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+}
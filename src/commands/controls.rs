@@ -0,0 +1,91 @@
+//! Hydrogen // Commands // Controls
+//!
+//! '/controls' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{all::CommandInteraction, builder::CreateCommand, client::Context};
+use tracing::error;
+
+use crate::{
+    handler::{Response, Result},
+    manager::HydrogenManagerError,
+    utils::{error_message, translate_command, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL,
+};
+
+/// Executes the `/controls` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "controls", "embed_title");
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Send the player controls.
+    match data.manager.show_controls(data.guild_id).await {
+        Ok(channel_id) => Ok(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "controls", "sent")
+                .replace("{channel}", &format!("<#{}>", channel_id)),
+        }),
+        Err(HydrogenManagerError::PlayerNotFound) => {
+            // The player doesn't exists.
+            Err(Response::Generic {
+                title,
+                description: error_message(
+                    &hydrogen.i18n,
+                    &interaction.locale,
+                    &hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "player_not_exists"),
+                ),
+            })
+        }
+        Err(e) => {
+            error!(
+                "cannot show the player controls in the guild {}: {}",
+                data.guild_id, e
+            );
+
+            Err(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            })
+        }
+    }
+}
+
+/// Registers the `/controls` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "controls",
+        "name",
+        "description",
+        CreateCommand::new("controls"),
+    )
+    .description("Show the player controls in the player's channel.")
+}
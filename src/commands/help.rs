@@ -0,0 +1,100 @@
+//! Hydrogen // Commands // Help
+//!
+//! '/help' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{all::CommandInteraction, builder::CreateCommand, client::Context};
+
+use crate::{
+    handler::{Response, Result},
+    utils::translate_command,
+    HydrogenContext,
+};
+
+/// The commands shown by `/help`, grouped by the translation key of the group's name.
+///
+/// The commands' names are also their translation categories.
+const HELP_GROUPS: [(&str, &[&str]); 3] = [
+    (
+        "group_music",
+        &[
+            "play",
+            "join",
+            "nowplaying",
+            "seek",
+            "replay",
+            "jump",
+            "shuffle",
+            "volume",
+            "controls",
+            "move",
+            "announce",
+            "settings",
+        ],
+    ),
+    ("group_dice", &["roll"]),
+    ("group_info", &["about", "help"]),
+];
+
+/// Executes the `/help` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    _: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    let commands_id = hydrogen.commands_id.read().await;
+
+    let description = HELP_GROUPS
+        .iter()
+        .map(|(group, commands)| {
+            let lines = commands
+                .iter()
+                .map(|command| {
+                    // Mention the command, allowing it to be clicked, if its ID is known.
+                    let mention = match commands_id.get(*command) {
+                        Some(id) => format!("</{}:{}>", command, id.get()),
+                        None => format!("`/{}`", command),
+                    };
+
+                    hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "help", "command")
+                        .replace("{command}", &mention)
+                        .replace(
+                            "{description}",
+                            &hydrogen
+                                .i18n
+                                .translate(&interaction.locale, command, "description"),
+                        )
+                })
+                .collect::<Vec<_>>();
+
+            format!(
+                "### {}\n{}",
+                hydrogen.i18n.translate(&interaction.locale, "help", group),
+                lines.join("\n")
+            )
+        })
+        .collect::<Vec<_>>();
+
+    Ok(Response::Generic {
+        title: hydrogen
+            .i18n
+            .translate(&interaction.locale, "help", "embed_title"),
+        description: description.join("\n"),
+    })
+}
+
+/// Registers the `/help` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "help",
+        "name",
+        "description",
+        CreateCommand::new("help"),
+    )
+    .description("Show the commands and what they do.")
+}
@@ -3,12 +3,16 @@
 //! '/join' command registration and execution.
 
 use hydrogen_i18n::I18n;
-use serenity::{all::CommandInteraction, builder::CreateCommand, client::Context};
+use serenity::{
+    all::{ChannelType, CommandInteraction, CommandOptionType, Permissions},
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+};
 use tracing::{error, warn};
 
 use crate::{
     handler::{Response, Result},
-    utils::{error_message, MusicCommonData},
+    utils::{error_message, translate_command, translate_option, MusicCommonData},
     HydrogenContext, HYDROGEN_BUG_URL,
 };
 
@@ -53,8 +57,15 @@ pub async fn execute(
         });
     }
 
-    // Get the user's voice channel ID.
-    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+    // Get the channel requested by the user, or the user's voice channel ID.
+    let Some(voice_channel_id) = interaction
+        .data
+        .options
+        .iter()
+        .find(|v| v.name == "channel")
+        .and_then(|v| v.value.as_channel_id())
+        .or_else(|| data.get_connected_channel(interaction.user.id))
+    else {
         warn!(
             "cannot get the voice channel ID of the user {} in the guild {}",
             interaction.user.id, data.guild_id
@@ -73,6 +84,38 @@ pub async fn execute(
         });
     };
 
+    // Check if the bot can connect to the voice channel.
+    let can_connect = data
+        .guild
+        .channels
+        .get(&voice_channel_id)
+        .zip(data.guild.members.get(&context.cache.current_user().id))
+        .map(|(channel, member)| {
+            data.guild
+                .user_permissions_in(channel, member)
+                .contains(Permissions::CONNECT)
+        })
+        .unwrap_or(false);
+
+    if !can_connect {
+        warn!(
+            "cannot connect to the voice channel {} in the guild {}: missing permissions",
+            voice_channel_id, data.guild_id
+        );
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "join", "no_permission")
+                    .replace("{channel}", &format!("<#{}>", voice_channel_id)),
+            ),
+        });
+    }
+
     // Join the voice channel.
     if let Err(e) = data
         .voice_manager
@@ -115,6 +158,14 @@ pub async fn execute(
             data.guild_id, e
         );
 
+        // Leave the voice channel joined for the player.
+        if let Err(e) = data.voice_manager.remove(data.guild_id).await {
+            warn!(
+                "cannot leave the voice channel in the guild {}: {}",
+                data.guild_id, e
+            );
+        }
+
         return Err(Response::Generic {
             title,
             description: hydrogen
@@ -143,14 +194,25 @@ pub async fn execute(
 ///
 /// If `i18n` is `None`, the translation will be ignored.
 pub fn register(i18n: Option<&I18n>) -> CreateCommand {
-    let mut command = CreateCommand::new("join");
-
-    if let Some(i18n) = i18n {
-        command = i18n.serenity_command_name("join", "name", command);
-        command = i18n.serenity_command_description("join", "description", command);
-    }
-
-    command
-        .description("Make me join your voice chat without playing anything.")
-        .dm_permission(false)
+    translate_command(
+        i18n,
+        "join",
+        "name",
+        "description",
+        CreateCommand::new("join"),
+    )
+    .description("Make me join your voice chat without playing anything.")
+    .add_option(translate_option(
+        i18n,
+        "join",
+        "channel_name",
+        "channel_description",
+        CreateCommandOption::new(
+            CommandOptionType::Channel,
+            "channel",
+            "The voice or stage channel to join, instead of your voice chat.",
+        )
+        .channel_types(vec![ChannelType::Voice, ChannelType::Stage])
+        .required(false),
+    ))
 }
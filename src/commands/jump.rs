@@ -0,0 +1,230 @@
+//! Hydrogen // Commands // Jump
+//!
+//! '/jump' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{
+    all::{CommandInteraction, CommandOptionType},
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+};
+use tracing::{error, warn};
+
+use crate::{
+    handler::{Response, Result},
+    player::HydrogenMusic,
+    utils::{error_message, translate_command, translate_option, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL,
+};
+
+/// Executes the `/jump` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "jump", "embed_title");
+
+    // Get the position option value, converting it to an index.
+    let Some(position) = interaction
+        .data
+        .options
+        .iter()
+        .find(|v| v.name == "position")
+        .and_then(|v| v.value.as_i64())
+        .and_then(|v| usize::try_from(v - 1).ok())
+    else {
+        error!("cannot get the 'position' option");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the destructive option value, defaulting to keep the skipped tracks.
+    let destructive = interaction
+        .data
+        .options
+        .iter()
+        .find(|v| v.name == "destructive")
+        .and_then(|v| v.value.as_bool())
+        .unwrap_or(false);
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the user's voice channel ID.
+    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+        warn!(
+            "cannot get the voice channel ID of the user {} in the guild {}",
+            interaction.user.id, data.guild_id
+        );
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown_voice_state")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            ),
+        });
+    };
+
+    // Get the player's voice channel ID.
+    if let Some(my_channel_id) = data.manager.get_voice_channel_id(data.guild_id).await {
+        // Checks if the user is in the same voice channel as the bot.
+        if my_channel_id == voice_channel_id.into() {
+            // Jump to the track.
+            let music = match data
+                .manager
+                .jump(data.guild_id, position, destructive)
+                .await
+            {
+                Ok(v) => v,
+                Err(e) => {
+                    error!(
+                        "cannot jump to the track in the guild {}: {}",
+                        data.guild_id, e
+                    );
+
+                    return Err(Response::Generic {
+                        title,
+                        description: hydrogen
+                            .i18n
+                            .translate(&interaction.locale, "error", "unknown")
+                            .replace("{url}", HYDROGEN_BUG_URL),
+                    });
+                }
+            };
+
+            // Get the music.
+            let Some(music) = music else {
+                warn!(
+                    "guild {} doesn't have a track in the position {}",
+                    data.guild_id, position
+                );
+
+                return Err(Response::Generic {
+                    title,
+                    description: error_message(
+                        &hydrogen.i18n,
+                        &interaction.locale,
+                        &hydrogen
+                            .i18n
+                            .translate(&interaction.locale, "jump", "invalid_position"),
+                    ),
+                });
+            };
+
+            Ok(Response::Generic {
+                title,
+                description: get_message(music, hydrogen, interaction),
+            })
+        } else {
+            // The user is not in the same voice channel as the bot.
+            Err(Response::Generic {
+                title,
+                description: error_message(
+                    &hydrogen.i18n,
+                    &interaction.locale,
+                    &hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "not_in_voice_chat"),
+                ),
+            })
+        }
+    } else {
+        // The player doesn't exists.
+        Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "player_not_exists"),
+            ),
+        })
+    }
+}
+
+/// Registers the `/jump` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "jump",
+        "name",
+        "description",
+        CreateCommand::new("jump"),
+    )
+    .description("Jump to a track in the queue.")
+    .add_option(translate_option(
+        i18n,
+        "jump",
+        "position_name",
+        "position_description",
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "position",
+            "The position of the track in the queue.",
+        )
+        .required(true)
+        .min_int_value(1),
+    ))
+    .add_option(translate_option(
+        i18n,
+        "jump",
+        "destructive_name",
+        "destructive_description",
+        CreateCommandOption::new(
+            CommandOptionType::Boolean,
+            "destructive",
+            "Remove the skipped songs from the queue when jumping forward.",
+        )
+        .required(false),
+    ))
+}
+
+/// Get the message to send to the user.
+fn get_message(
+    track: HydrogenMusic,
+    hydrogen: &HydrogenContext,
+    interaction: &CommandInteraction,
+) -> String {
+    if let Some(uri) = track.uri {
+        hydrogen
+            .i18n
+            .translate(&interaction.locale, "jump", "jumping_url")
+            .replace("{name}", &track.title)
+            .replace("{author}", &track.author)
+            .replace("{url}", &uri)
+    } else {
+        hydrogen
+            .i18n
+            .translate(&interaction.locale, "jump", "jumping")
+            .replace("{name}", &track.title)
+            .replace("{author}", &track.author)
+    }
+}
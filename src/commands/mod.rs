@@ -3,7 +3,17 @@
 //! This module contains all the commands from Hydrogen.
 
 pub mod about;
+pub mod announce;
+pub mod controls;
+pub mod help;
 pub mod join;
+pub mod jump;
+pub mod move_message;
+pub mod nowplaying;
 pub mod play;
+pub mod replay;
 pub mod roll;
 pub mod seek;
+pub mod settings;
+pub mod shuffle;
+pub mod volume;
@@ -0,0 +1,163 @@
+//! Hydrogen // Commands // Move
+//!
+//! '/move' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{
+    all::{ChannelType, CommandInteraction, CommandOptionType, Permissions},
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+};
+use tracing::{error, warn};
+
+use crate::{
+    handler::{Response, Result},
+    manager::HydrogenManagerError,
+    utils::{error_message, translate_command, translate_option, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL,
+};
+
+/// Executes the `/move` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "move", "embed_title");
+
+    // Get the channel option value.
+    let Some(channel_id) = interaction
+        .data
+        .options
+        .first()
+        .and_then(|v| v.value.as_channel_id())
+    else {
+        error!("cannot get the 'channel' option");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Check if the bot can send the player message in the channel.
+    let can_send = data
+        .guild
+        .channels
+        .get(&channel_id)
+        .zip(data.guild.members.get(&context.cache.current_user().id))
+        .map(|(channel, member)| {
+            data.guild.user_permissions_in(channel, member).contains(
+                Permissions::VIEW_CHANNEL | Permissions::SEND_MESSAGES | Permissions::EMBED_LINKS,
+            )
+        })
+        .unwrap_or(false);
+
+    if !can_send {
+        warn!(
+            "cannot send messages in the channel {} in the guild {}: missing permissions",
+            channel_id, data.guild_id
+        );
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "move", "no_permission")
+                    .replace("{channel}", &format!("<#{}>", channel_id)),
+            ),
+        });
+    }
+
+    // Move the player message.
+    match data.manager.move_message(data.guild_id, channel_id).await {
+        Ok(()) => Ok(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "move", "moved")
+                .replace("{channel}", &format!("<#{}>", channel_id)),
+        }),
+        Err(HydrogenManagerError::PlayerNotFound) => {
+            // The player doesn't exists.
+            Err(Response::Generic {
+                title,
+                description: error_message(
+                    &hydrogen.i18n,
+                    &interaction.locale,
+                    &hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "player_not_exists"),
+                ),
+            })
+        }
+        Err(e) => {
+            error!(
+                "cannot move the player message in the guild {}: {}",
+                data.guild_id, e
+            );
+
+            Err(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            })
+        }
+    }
+}
+
+/// Registers the `/move` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "move",
+        "name",
+        "description",
+        CreateCommand::new("move"),
+    )
+    .description("Move the player message to another channel.")
+    .add_option(translate_option(
+        i18n,
+        "move",
+        "channel_name",
+        "channel_description",
+        CreateCommandOption::new(
+            CommandOptionType::Channel,
+            "channel",
+            "The channel where the player message will be sent.",
+        )
+        .channel_types(vec![
+            ChannelType::Text,
+            ChannelType::News,
+            ChannelType::Voice,
+        ])
+        .required(true),
+    ))
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+}
@@ -0,0 +1,136 @@
+//! Hydrogen // Commands // Now Playing
+//!
+//! '/nowplaying' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{all::CommandInteraction, builder::CreateCommand, client::Context};
+use tracing::{error, warn};
+
+use crate::{
+    handler::{Response, Result},
+    manager::HydrogenManagerError,
+    player::{HydrogenNowPlaying, TrackProgress},
+    utils::{error_message, progress_bar, time_to_string, translate_command, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL,
+};
+
+/// Executes the `/nowplaying` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "nowplaying", "embed_title");
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the current track and its position.
+    let now_playing = match data.manager.now_with_position(data.guild_id).await {
+        Ok(v) => v,
+        Err(HydrogenManagerError::PlayerNotFound) => {
+            // The player doesn't exists.
+            return Err(Response::Generic {
+                title,
+                description: error_message(
+                    &hydrogen.i18n,
+                    &interaction.locale,
+                    &hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "player_not_exists"),
+                ),
+            });
+        }
+        Err(e) => {
+            error!(
+                "cannot get the current track in the guild {}: {}",
+                data.guild_id, e
+            );
+
+            return Err(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            });
+        }
+    };
+
+    // Get the current track.
+    let Some(now_playing) = now_playing else {
+        warn!("guild {} isn't playing anything", data.guild_id);
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "nowplaying", "nothing"),
+            ),
+        });
+    };
+
+    Ok(Response::Generic {
+        title,
+        description: get_message(now_playing, hydrogen, interaction),
+    })
+}
+
+/// Registers the `/nowplaying` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "nowplaying",
+        "name",
+        "description",
+        CreateCommand::new("nowplaying"),
+    )
+    .description("Show the song currently playing and its progress.")
+}
+
+/// Get the message to send to the user.
+fn get_message(
+    now_playing: HydrogenNowPlaying,
+    hydrogen: &HydrogenContext,
+    interaction: &CommandInteraction,
+) -> String {
+    // Get the current time, total time and progress bar.
+    let current_time = time_to_string(now_playing.position / 1000);
+    let total_time = time_to_string(now_playing.total / 1000);
+    let progress_bar = progress_bar(now_playing.progress_ratio());
+
+    if let Some(uri) = now_playing.track.uri {
+        hydrogen
+            .i18n
+            .translate(&interaction.locale, "nowplaying", "playing_url")
+            .replace("{url}", &uri)
+    } else {
+        hydrogen
+            .i18n
+            .translate(&interaction.locale, "nowplaying", "playing")
+    }
+    .replace("{position}", &(now_playing.index + 1).to_string())
+    .replace("{name}", &now_playing.track.title)
+    .replace("{author}", &now_playing.track.author)
+    .replace("{current}", &current_time)
+    .replace("{total}", &total_time)
+    .replace("{progress}", &progress_bar)
+}
@@ -11,9 +11,14 @@ use serenity::{
 use tracing::{error, warn};
 
 use crate::{
-    handler::{Response, Result},
+    handler::{set_state, Invoker, Response, Result},
+    lavalink::rest::LavalinkSeverity,
+    manager::HydrogenManagerError,
     player::HydrogenPlayCommand,
-    utils::{error_message, get_str_option, MusicCommonData},
+    utils::{
+        artwork_url, error_message, get_str_option, translate_command, translate_option,
+        wait_for_connection, MusicCommonData,
+    },
     HydrogenContext, HYDROGEN_BUG_URL,
 };
 
@@ -28,7 +33,7 @@ pub async fn execute(
         .i18n
         .translate(&interaction.locale, "play", "embed_title");
 
-    // Get the time option value.
+    // Get the query option value.
     let Some(query) = get_str_option(interaction, 0) else {
         error!("cannot get the 'query' option");
 
@@ -41,34 +46,53 @@ pub async fn execute(
         });
     };
 
+    play(hydrogen, context, &interaction.into(), query, false).await
+}
+
+/// Plays the query, used by the `/play` command, the prefix commands and the `duplicate_add` component.
+///
+/// If `allow_duplicate` is `false` and the guild asks about duplicates, the track isn't enqueued if it's already in
+/// the queue, asking the user to confirm it instead.
+pub async fn play(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    invoker: &Invoker,
+    query: &str,
+    allow_duplicate: bool,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(invoker.locale, "play", "embed_title");
+
     // Get the common data used by music commands and components.
-    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+    let Some(data) = MusicCommonData::new(hydrogen, context, invoker.guild_id).await else {
         error!("cannot get common music data");
 
         return Err(Response::Generic {
             title,
             description: hydrogen
                 .i18n
-                .translate(&interaction.locale, "error", "unknown")
+                .translate(invoker.locale, "error", "unknown")
                 .replace("{url}", HYDROGEN_BUG_URL),
         });
     };
 
     // Get the user's voice channel ID.
-    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+    let Some(voice_channel_id) = data.get_connected_channel(invoker.user_id) else {
         warn!(
             "cannot get the voice channel ID of the user {} in the guild {}",
-            interaction.user.id, data.guild_id
+            invoker.user_id, data.guild_id
         );
 
         return Err(Response::Generic {
             title,
             description: error_message(
                 &hydrogen.i18n,
-                &interaction.locale,
+                invoker.locale,
                 &hydrogen
                     .i18n
-                    .translate(&interaction.locale, "error", "unknown_voice_state")
+                    .translate(invoker.locale, "error", "unknown_voice_state")
                     .replace("{url}", HYDROGEN_BUG_URL),
             ),
         });
@@ -98,12 +122,10 @@ pub async fn execute(
                             title,
                             description: error_message(
                                 &hydrogen.i18n,
-                                &interaction.locale,
-                                &hydrogen.i18n.translate(
-                                    &interaction.locale,
-                                    "error",
-                                    "cant_connect",
-                                ),
+                                invoker.locale,
+                                &hydrogen
+                                    .i18n
+                                    .translate(invoker.locale, "error", "cant_connect"),
                             ),
                         });
                     }
@@ -130,10 +152,10 @@ pub async fn execute(
                         title,
                         description: error_message(
                             &hydrogen.i18n,
-                            &interaction.locale,
+                            invoker.locale,
                             &hydrogen
                                 .i18n
-                                .translate(&interaction.locale, "error", "cant_connect"),
+                                .translate(invoker.locale, "error", "cant_connect"),
                         ),
                     });
                 }
@@ -141,6 +163,25 @@ pub async fn execute(
         }
     };
 
+    // Wait for the voice connection to be established.
+    if !wait_for_connection(&call).await {
+        warn!(
+            "voice connection not established in time in the guild {}",
+            data.guild_id
+        );
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                invoker.locale,
+                &hydrogen
+                    .i18n
+                    .translate(invoker.locale, "error", "connection_timeout"),
+            ),
+        });
+    }
+
     // Fetch the connection info.
     if let Some(connection_info) = call.lock().await.current_connection() {
         if let Some(channel_id) = connection_info.channel_id {
@@ -150,10 +191,10 @@ pub async fn execute(
                     title,
                     description: error_message(
                         &hydrogen.i18n,
-                        &interaction.locale,
+                        invoker.locale,
                         &hydrogen
                             .i18n
-                            .translate(&interaction.locale, "error", "not_in_voice_chat"),
+                            .translate(invoker.locale, "error", "not_in_voice_chat"),
                     ),
                 });
             }
@@ -165,18 +206,71 @@ pub async fn execute(
         .manager
         .init_or_play(
             data.guild_id,
-            &interaction
-                .guild_locale
-                .clone()
-                .unwrap_or(interaction.locale.clone()),
+            invoker.guild_locale,
             query,
-            interaction.user.id,
+            invoker.user_id,
             data.voice_manager.clone(),
-            interaction.channel_id,
+            invoker.channel_id,
+            allow_duplicate,
         )
         .await
     {
         Ok(e) => e,
+        Err(HydrogenManagerError::DuplicateTrack) => {
+            // Keep the query to be enqueued if the user confirms it.
+            set_state(
+                &hydrogen.pending_duplicates,
+                (invoker.channel_id, invoker.user_id),
+                query.to_owned(),
+            );
+
+            return Err(Response::Confirm {
+                title,
+                description: hydrogen.i18n.translate(invoker.locale, "play", "duplicate"),
+                id: "duplicate_add",
+                label: hydrogen
+                    .i18n
+                    .translate(invoker.locale, "play", "duplicate_add"),
+            });
+        }
+        Err(HydrogenManagerError::NodesBusy) => {
+            warn!(
+                "cannot create a player in the guild {}: every lavalink node is at capacity",
+                data.guild_id
+            );
+
+            // No player was created, so leave the voice channel joined for it.
+            if let Err(e) = data.voice_manager.remove(data.guild_id).await {
+                warn!(
+                    "cannot leave the voice channel in the guild {}: {}",
+                    data.guild_id, e
+                );
+            }
+
+            return Err(Response::Generic {
+                title,
+                description: error_message(
+                    &hydrogen.i18n,
+                    invoker.locale,
+                    &hydrogen
+                        .i18n
+                        .translate(invoker.locale, "play", "nodes_busy"),
+                ),
+            });
+        }
+        Err(HydrogenManagerError::RequesterLeft) => {
+            warn!(
+                "user {} left the voice channel while loading the music in the guild {}",
+                invoker.user_id, data.guild_id
+            );
+
+            return Err(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(invoker.locale, "play", "requester_left"),
+            });
+        }
         Err(e) => {
             error!(
                 "cannot play the music in the guild {}: {}",
@@ -187,10 +281,10 @@ pub async fn execute(
                 title,
                 description: error_message(
                     &hydrogen.i18n,
-                    &interaction.locale,
+                    invoker.locale,
                     &hydrogen
                         .i18n
-                        .translate(&interaction.locale, "error", "unknown")
+                        .translate(invoker.locale, "error", "unknown")
                         .replace("{url}", HYDROGEN_BUG_URL),
                 ),
             });
@@ -198,11 +292,56 @@ pub async fn execute(
     };
 
     if result.count > 0 {
-        // Success.
-        Ok(Response::Generic {
-            title,
-            description: get_message(result, hydrogen, interaction),
+        // Success, showing the artwork of the loaded track.
+        let thumbnail = result.track.as_ref().map(|v| artwork_url(v).to_owned());
+        let description = get_message(result, hydrogen, invoker);
+
+        Ok(match thumbnail {
+            Some(thumbnail) => Response::Track {
+                title,
+                description,
+                thumbnail,
+            },
+            None => Response::Generic { title, description },
         })
+    } else if let Some(exception) = result.exception {
+        // Lavalink failed to load the track.
+        warn!(
+            "cannot load the track in the guild {}: {:?} {}",
+            data.guild_id, exception.severity, exception.cause
+        );
+
+        let description = if exception.is_region_locked() {
+            // The track exists, but the node's region can't play it.
+            error_message(
+                &hydrogen.i18n,
+                invoker.locale,
+                &hydrogen
+                    .i18n
+                    .translate(invoker.locale, "play", "region_locked"),
+            )
+        } else if exception.severity == LavalinkSeverity::Common {
+            // The track is unavailable, like age-restricted or blocked in the region.
+            error_message(
+                &hydrogen.i18n,
+                invoker.locale,
+                &hydrogen
+                    .i18n
+                    .translate(invoker.locale, "play", "unavailable"),
+            )
+        } else {
+            // Something is wrong with Lavalink or the source.
+            error_message(
+                &hydrogen.i18n,
+                invoker.locale,
+                &hydrogen
+                    .i18n
+                    .translate(invoker.locale, "error", "unknown")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            )
+        };
+
+        Err(Response::Generic { title, description })
     } else {
         // Error.
         if !result.truncated {
@@ -211,10 +350,8 @@ pub async fn execute(
                 title,
                 description: error_message(
                     &hydrogen.i18n,
-                    &interaction.locale,
-                    &hydrogen
-                        .i18n
-                        .translate(&interaction.locale, "play", "not_found"),
+                    invoker.locale,
+                    &hydrogen.i18n.translate(invoker.locale, "play", "not_found"),
                 ),
             })
         } else {
@@ -223,10 +360,16 @@ pub async fn execute(
                 title,
                 description: error_message(
                     &hydrogen.i18n,
-                    &interaction.locale,
-                    &hydrogen
-                        .i18n
-                        .translate(&interaction.locale, "play", "truncated"),
+                    invoker.locale,
+                    &hydrogen.i18n.translate(
+                        invoker.locale,
+                        "play",
+                        if result.user_limited {
+                            "user_truncated"
+                        } else {
+                            "truncated"
+                        },
+                    ),
                 ),
             })
         }
@@ -237,59 +380,62 @@ pub async fn execute(
 ///
 /// If `i18n` is `None`, the translation will be ignored.
 pub fn register(i18n: Option<&I18n>) -> CreateCommand {
-    let mut command = CreateCommand::new("play");
-
-    if let Some(i18n) = i18n {
-        command = i18n.serenity_command_name("play", "name", command);
-        command = i18n.serenity_command_description("play", "description", command);
-    }
-
-    command
-            .description(
-                "Request music to be played, enqueuing it in the queue or playing immediately if empty.",
-            )
-            .add_option({
-                let mut option = CreateCommandOption::new(
-                    CommandOptionType::String,
-                    "query",
-                    "A music or playlist URL, or a search term.",
-                )
-                .required(true);
-
-                if let Some(i18n) = i18n {
-                    option =
-                        i18n.serenity_command_option_name("play", "query_name", option);
-                    option = i18n.serenity_command_option_description(
-                        "play",
-                        "query_description",
-                        option,
-                    );
-                }
-
-                option
-            })
-            .dm_permission(false)
+    translate_command(
+        i18n,
+        "play",
+        "name",
+        "description",
+        CreateCommand::new("play"),
+    )
+    .description(
+        "Request music to be played, enqueuing it in the queue or playing immediately if empty.",
+    )
+    .add_option(translate_option(
+        i18n,
+        "play",
+        "query_name",
+        "query_description",
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "query",
+            "A music or playlist URL, or a search term.",
+        )
+        .required(true),
+    ))
 }
 
 /// Get the message to send to the user.
 fn get_message(
     result: HydrogenPlayCommand,
     hydrogen: &HydrogenContext,
-    interaction: &CommandInteraction,
+    invoker: &Invoker,
 ) -> String {
+    // Tell the user which limit removed the tracks.
+    let truncated_warn = if result.user_limited {
+        "user_truncated_warn"
+    } else {
+        "truncated_warn"
+    };
+
+    // Use the messages containing the playlist's name when the source provides it.
+    let (playlist_suffix, playlist_name) = match result.playlist_name.as_deref() {
+        Some(name) => ("_playlist", name),
+        None => ("", ""),
+    };
+
     if let Some(track) = result.track {
         if result.playing && result.count == 1 {
             if let Some(uri) = track.uri {
                 return hydrogen
                     .i18n
-                    .translate(&interaction.locale, "play", "play_single_url")
+                    .translate(invoker.locale, "play", "play_single_url")
                     .replace("{name}", &track.title)
                     .replace("{author}", &track.author)
                     .replace("{url}", &uri);
             } else {
                 return hydrogen
                     .i18n
-                    .translate(&interaction.locale, "play", "play_single")
+                    .translate(invoker.locale, "play", "play_single")
                     .replace("{name}", &track.title)
                     .replace("{author}", &track.author);
             }
@@ -297,14 +443,14 @@ fn get_message(
             if let Some(uri) = track.uri {
                 return hydrogen
                     .i18n
-                    .translate(&interaction.locale, "play", "enqueue_single_url")
+                    .translate(invoker.locale, "play", "enqueue_single_url")
                     .replace("{name}", &track.title)
                     .replace("{author}", &track.author)
                     .replace("{url}", &uri);
             } else {
                 return hydrogen
                     .i18n
-                    .translate(&interaction.locale, "play", "enqueue_single")
+                    .translate(invoker.locale, "play", "enqueue_single")
                     .replace("{name}", &track.title)
                     .replace("{author}", &track.author);
             }
@@ -313,7 +459,12 @@ fn get_message(
                 if let Some(uri) = track.uri {
                     return hydrogen
                         .i18n
-                        .translate(&interaction.locale, "play", "play_multi_url")
+                        .translate(
+                            invoker.locale,
+                            "play",
+                            &format!("play_multi{}_url", playlist_suffix),
+                        )
+                        .replace("{playlist}", playlist_name)
                         .replace("{name}", &track.title)
                         .replace("{author}", &track.author)
                         .replace("{url}", &uri)
@@ -321,7 +472,12 @@ fn get_message(
                 } else {
                     return hydrogen
                         .i18n
-                        .translate(&interaction.locale, "play", "play_multi")
+                        .translate(
+                            invoker.locale,
+                            "play",
+                            &format!("play_multi{}", playlist_suffix),
+                        )
+                        .replace("{playlist}", playlist_name)
                         .replace("{name}", &track.title)
                         .replace("{author}", &track.author)
                         .replace("{count}", &result.count.to_string());
@@ -331,10 +487,15 @@ fn get_message(
                     "{}\n\n{}",
                     hydrogen
                         .i18n
-                        .translate(&interaction.locale, "play", "truncated_warn",),
+                        .translate(invoker.locale, "play", truncated_warn),
                     hydrogen
                         .i18n
-                        .translate(&interaction.locale, "play", "play_multi_url",)
+                        .translate(
+                            invoker.locale,
+                            "play",
+                            &format!("play_multi{}_url", playlist_suffix)
+                        )
+                        .replace("{playlist}", playlist_name)
                         .replace("{name}", &track.title)
                         .replace("{author}", &track.author)
                         .replace("{url}", &uri)
@@ -345,10 +506,15 @@ fn get_message(
                     "{}\n\n{}",
                     hydrogen
                         .i18n
-                        .translate(&interaction.locale, "play", "truncated_warn",),
+                        .translate(invoker.locale, "play", truncated_warn),
                     hydrogen
                         .i18n
-                        .translate(&interaction.locale, "play", "play_multi")
+                        .translate(
+                            invoker.locale,
+                            "play",
+                            &format!("play_multi{}", playlist_suffix)
+                        )
+                        .replace("{playlist}", playlist_name)
                         .replace("{name}", &track.title)
                         .replace("{author}", &track.author)
                         .replace("{count}", &result.count.to_string())
@@ -362,16 +528,26 @@ fn get_message(
             "{}\n\n{}",
             hydrogen
                 .i18n
-                .translate(&interaction.locale, "play", "truncated_warn",),
+                .translate(invoker.locale, "play", truncated_warn),
             hydrogen
                 .i18n
-                .translate(&interaction.locale, "play", "enqueue_multi")
+                .translate(
+                    invoker.locale,
+                    "play",
+                    &format!("enqueue_multi{}", playlist_suffix)
+                )
+                .replace("{playlist}", playlist_name)
                 .replace("{count}", &result.count.to_string())
         );
     }
 
     hydrogen
         .i18n
-        .translate(&interaction.locale, "play", "enqueue_multi")
+        .translate(
+            invoker.locale,
+            "play",
+            &format!("enqueue_multi{}", playlist_suffix),
+        )
+        .replace("{playlist}", playlist_name)
         .replace("{count}", &result.count.to_string())
 }
@@ -0,0 +1,165 @@
+//! Hydrogen // Commands // Replay
+//!
+//! '/replay' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{all::CommandInteraction, builder::CreateCommand, client::Context};
+use tracing::{error, warn};
+
+use crate::{
+    handler::{Response, Result},
+    player::HydrogenMusic,
+    utils::{error_message, translate_command, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL,
+};
+
+/// Executes the `/replay` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "replay", "embed_title");
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the user's voice channel ID.
+    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+        warn!(
+            "cannot get the voice channel ID of the user {} in the guild {}",
+            interaction.user.id, data.guild_id
+        );
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown_voice_state")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            ),
+        });
+    };
+
+    // Get the player's voice channel ID.
+    if let Some(my_channel_id) = data.manager.get_voice_channel_id(data.guild_id).await {
+        // Checks if the user is in the same voice channel as the bot.
+        if my_channel_id == voice_channel_id.into() {
+            // Seek the player to the beginning of the track.
+            let seek_result = match data.manager.seek(data.guild_id, 0).await {
+                Ok(Some(v)) => v,
+                Ok(None) => {
+                    // The queue is empty.
+                    warn!("guild {} has a empty queue", data.guild_id);
+
+                    return Err(Response::Generic {
+                        title,
+                        description: error_message(
+                            &hydrogen.i18n,
+                            &interaction.locale,
+                            &hydrogen
+                                .i18n
+                                .translate(&interaction.locale, "error", "empty_queue"),
+                        ),
+                    });
+                }
+                Err(e) => {
+                    // An error occurred.
+                    error!(
+                        "cannot replay the track in the guild {}: {}",
+                        data.guild_id, e
+                    );
+
+                    return Err(Response::Generic {
+                        title,
+                        description: hydrogen
+                            .i18n
+                            .translate(&interaction.locale, "error", "unknown")
+                            .replace("{url}", HYDROGEN_BUG_URL),
+                    });
+                }
+            };
+
+            Ok(Response::Generic {
+                title,
+                description: get_message(seek_result.track, hydrogen, interaction),
+            })
+        } else {
+            // The user is not in the same voice channel as the bot.
+            Err(Response::Generic {
+                title,
+                description: error_message(
+                    &hydrogen.i18n,
+                    &interaction.locale,
+                    &hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "not_in_voice_chat"),
+                ),
+            })
+        }
+    } else {
+        // The player doesn't exists.
+        Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "player_not_exists"),
+            ),
+        })
+    }
+}
+
+/// Registers the `/replay` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "replay",
+        "name",
+        "description",
+        CreateCommand::new("replay"),
+    )
+    .description("Restart the current song from the beginning.")
+}
+
+/// Get the message to send to the user.
+fn get_message(
+    track: HydrogenMusic,
+    hydrogen: &HydrogenContext,
+    interaction: &CommandInteraction,
+) -> String {
+    if let Some(uri) = track.uri {
+        hydrogen
+            .i18n
+            .translate(&interaction.locale, "replay", "replaying_url")
+            .replace("{name}", &track.title)
+            .replace("{author}", &track.author)
+            .replace("{url}", &uri)
+    } else {
+        hydrogen
+            .i18n
+            .translate(&interaction.locale, "replay", "replaying")
+            .replace("{name}", &track.title)
+            .replace("{author}", &track.author)
+    }
+}
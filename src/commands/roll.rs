@@ -12,8 +12,9 @@ use tracing::error;
 
 use crate::{
     handler::{Response, Result},
-    roll::{DiceType, Params},
-    HydrogenContext, HYDROGEN_BUG_URL,
+    roll::{DiceType, Error, Format, Params},
+    utils::{translate_command, translate_option},
+    HydrogenContext, HYDROGEN_BUG_URL, HYDROGEN_EMBED_DESCRIPTION_LIMIT,
 };
 
 /// Executes the `/roll` command.
@@ -116,12 +117,41 @@ pub async fn execute(
 
             params
         }
+        "again" => match hydrogen.last_rolls.get(&interaction.user.id) {
+            Some(params) => params,
+            None => {
+                return Err(Response::Generic {
+                    title,
+                    description: hydrogen.i18n.translate(
+                        &interaction.locale,
+                        "roll",
+                        "no_previous",
+                    ),
+                });
+            }
+        },
         _ => unreachable!(),
     };
 
+    // Get the output format, the index is different for each sub-command, so it's found by name.
+    let format = sub_command_data
+        .iter()
+        .find(|v| v.name == "format")
+        .and_then(|v| v.value.as_str())
+        .and_then(Format::from_name)
+        .unwrap_or_default();
+
     // Roll the dice.
     let result = match params.roll() {
         Ok(v) => v,
+        Err(Error::DivideByZero) => {
+            return Err(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "roll", "divide_by_zero"),
+            });
+        }
         Err(e) => {
             error!(
                 "cannot roll the dice for the user {}: {}",
@@ -138,9 +168,17 @@ pub async fn execute(
         }
     };
 
+    // Store the parameters to be rolled again.
+    hydrogen.last_rolls.insert(interaction.user.id, params);
+
     Ok(Response::Generic {
         title,
-        description: result.to_string(),
+        description: result.to_limited_string(format, HYDROGEN_EMBED_DESCRIPTION_LIMIT, |count| {
+            hydrogen
+                .i18n
+                .translate(&interaction.locale, "roll", "truncated")
+                .replace("{count}", &count.to_string())
+        }),
     })
 }
 
@@ -149,123 +187,139 @@ pub async fn execute(
 /// If `i18n` is `None`, the translation will be ignored.
 pub fn register(i18n: Option<&I18n>) -> CreateCommand {
     // Create the option for the number of sides on the dice.
-    let mut dice_side_option = CreateCommandOption::new(
-        CommandOptionType::Integer,
-        "sides",
-        "The amount of sides the dice will have.",
-    )
-    .required(true)
-    .max_int_value(100)
-    .min_int_value(2);
+    let dice_side_option = translate_option(
+        i18n,
+        "roll",
+        "dice_sides_name",
+        "dice_sides_description",
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "sides",
+            "The amount of sides the dice will have.",
+        )
+        .required(true)
+        .max_int_value(100)
+        .min_int_value(2),
+    );
 
     // Create the option for the number of dice to roll.
-    let mut dice_count_option = CreateCommandOption::new(
-        CommandOptionType::Integer,
-        "dice_count",
-        "The amount of dices to roll.",
-    )
-    .required(false)
-    .max_int_value(50)
-    .min_int_value(1);
+    let dice_count_option = translate_option(
+        i18n,
+        "roll",
+        "dice_count_name",
+        "dice_count_description",
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "dice_count",
+            "The amount of dices to roll.",
+        )
+        .required(false)
+        .max_int_value(50)
+        .min_int_value(1),
+    );
 
     // Create the option for the number of times to roll the dices.
-    let mut roll_repeat_option = CreateCommandOption::new(
-        CommandOptionType::Integer,
-        "repetitions",
-        "The amount of times to roll the dices.",
-    )
-    .required(false)
-    .max_int_value(10)
-    .min_int_value(1);
+    let roll_repeat_option = translate_option(
+        i18n,
+        "roll",
+        "repetitions_name",
+        "repetitions_description",
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "repetitions",
+            "The amount of times to roll the dices.",
+        )
+        .required(false)
+        .max_int_value(10)
+        .min_int_value(1),
+    );
 
     // Create the option for the modifier.
-    let mut modifier_option = CreateCommandOption::new(
-        CommandOptionType::String,
-        "modifier",
-        "The modifier to add to the roll. (Need to be modifier like +2 or -2)",
-    )
-    .required(false);
-
-    // Translate the options.
-    if let Some(i18n) = i18n {
-        // Translate the dice side option.
-        dice_side_option =
-            i18n.serenity_command_option_name("roll", "dice_sides_name", dice_side_option);
-        dice_side_option = i18n.serenity_command_option_description(
-            "roll",
-            "dice_sides_description",
-            dice_side_option,
-        );
-
-        // Translate the dice count option.
-        dice_count_option =
-            i18n.serenity_command_option_name("roll", "dice_count_name", dice_count_option);
-        dice_count_option = i18n.serenity_command_option_description(
-            "roll",
-            "dice_count_description",
-            dice_count_option,
-        );
-
-        // Translate the roll repeat option.
-        roll_repeat_option =
-            i18n.serenity_command_option_name("roll", "repetitions_name", roll_repeat_option);
-        roll_repeat_option = i18n.serenity_command_option_description(
-            "roll",
-            "repetitions_description",
-            roll_repeat_option,
-        );
-
-        // Translate the modifier option.
-        modifier_option =
-            i18n.serenity_command_option_name("roll", "modifier_name", modifier_option);
-        modifier_option = i18n.serenity_command_option_description(
-            "roll",
-            "modifier_description",
-            modifier_option,
-        );
-    }
+    let modifier_option = translate_option(
+        i18n,
+        "roll",
+        "modifier_name",
+        "modifier_description",
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "modifier",
+            "The modifier to add to the roll. (Need to be modifier like +2 or -2)",
+        )
+        .required(false),
+    );
+
+    // Create the option for the output format.
+    let format_option = translate_option(
+        i18n,
+        "roll",
+        "format_name",
+        "format_description",
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "format",
+            "How the results are shown, the default is detailed.",
+        )
+        .required(false)
+        .add_string_choice("Detailed", Format::Detailed.name())
+        .add_string_choice("Sum", Format::Sum.name())
+        .add_string_choice("Compact", Format::Compact.name()),
+    );
 
     // Create the fate sub-command.
-    let mut fate_command =
+    let fate_command = translate_option(
+        i18n,
+        "roll",
+        "fate_name",
+        "fate_description",
         CreateCommandOption::new(CommandOptionType::SubCommand, "fate", "Roll a fate dice.")
             .add_sub_option(dice_count_option.clone())
             .add_sub_option(roll_repeat_option.clone())
-            .add_sub_option(modifier_option.clone());
+            .add_sub_option(modifier_option.clone())
+            .add_sub_option(format_option.clone()),
+    );
 
     // Create the sides sub-command.
-    let mut sided_command = CreateCommandOption::new(
-        CommandOptionType::SubCommand,
-        "sided",
-        "Roll a dice with a specific number of sides.",
-    )
-    .add_sub_option(dice_side_option)
-    .add_sub_option(dice_count_option)
-    .add_sub_option(roll_repeat_option)
-    .add_sub_option(modifier_option);
-
-    // Translate the sub-commands.
-    if let Some(i18n) = i18n {
-        // Translate the fate sub-command.
-        fate_command = i18n.serenity_command_option_name("roll", "fate_name", fate_command);
-        fate_command =
-            i18n.serenity_command_option_description("roll", "fate_description", fate_command);
-
-        // Translate the sided sub-command.
-        sided_command = i18n.serenity_command_option_name("roll", "sided_name", sided_command);
-        sided_command =
-            i18n.serenity_command_option_description("roll", "sided_description", sided_command);
-    }
+    let sided_command = translate_option(
+        i18n,
+        "roll",
+        "sided_name",
+        "sided_description",
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "sided",
+            "Roll a dice with a specific number of sides.",
+        )
+        .add_sub_option(dice_side_option)
+        .add_sub_option(dice_count_option)
+        .add_sub_option(roll_repeat_option)
+        .add_sub_option(modifier_option)
+        .add_sub_option(format_option.clone()),
+    );
+
+    // Create the again sub-command.
+    let again_command = translate_option(
+        i18n,
+        "roll",
+        "again_name",
+        "again_description",
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "again",
+            "Roll your last roll again.",
+        )
+        .add_sub_option(format_option),
+    );
 
     // Create the roll command.
-    let mut command = CreateCommand::new("roll")
-        .add_option(fate_command)
-        .add_option(sided_command);
-
-    // Translate the command.
-    if let Some(i18n) = i18n {
-        command = i18n.serenity_command_name("roll", "name", command);
-        command = i18n.serenity_command_description("roll", "description", command);
-    }
-
-    command.description("Roll a dice.")
+    translate_command(
+        i18n,
+        "roll",
+        "name",
+        "description",
+        CreateCommand::new("roll"),
+    )
+    .add_option(fate_command)
+    .add_option(sided_command)
+    .add_option(again_command)
+    .description("Roll a dice.")
 }
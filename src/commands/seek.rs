@@ -8,11 +8,17 @@ use serenity::{
     builder::{CreateCommand, CreateCommandOption},
     client::Context,
 };
-use tracing::{error, warn};
+use tracing::{debug, error, warn};
 
 use crate::{
     handler::{Response, Result},
-    utils::{error_message, get_str_option, progress_bar, time_to_string, MusicCommonData},
+    manager::HydrogenManagerError,
+    parsers::TimeParser,
+    player::{HydrogenPlayerError, TrackProgress},
+    utils::{
+        error_message, get_str_option, progress_bar, time_to_string, translate_command,
+        translate_option, MusicCommonData,
+    },
     HydrogenContext, HYDROGEN_BUG_URL,
 };
 
@@ -102,6 +108,11 @@ pub async fn execute(
                 },
             };
 
+            debug!(
+                "seeking the player in the guild {} to {}ms",
+                data.guild_id, seek_time
+            );
+
             // Convert the seek time to a i32 for the player.
             // TODO: Remove this when the player supports u32.
             let converted_seek_time = match seek_time.try_into() {
@@ -137,6 +148,27 @@ pub async fn execute(
                         ),
                     });
                 }
+                Err(HydrogenManagerError::Player(HydrogenPlayerError::SeekBeyondLength(
+                    length,
+                ))) => {
+                    // The time is beyond the end of the track.
+                    warn!(
+                        "cannot seek the player in the guild {} beyond the track length",
+                        data.guild_id
+                    );
+
+                    return Err(Response::Generic {
+                        title,
+                        description: error_message(
+                            &hydrogen.i18n,
+                            &interaction.locale,
+                            &hydrogen
+                                .i18n
+                                .translate(&interaction.locale, "seek", "beyond_length")
+                                .replace("{total}", &time_to_string(length / 1000)),
+                        ),
+                    });
+                }
                 Err(e) => {
                     // An error occurred.
                     error!(
@@ -157,7 +189,11 @@ pub async fn execute(
             // Get the current time, total time and progress bar.
             let current_time = time_to_string(seek_result.position / 1000);
             let total_time = time_to_string(seek_result.total / 1000);
-            let progress_bar = progress_bar(seek_result.position, seek_result.total);
+            let progress_bar = progress_bar(seek_result.progress_ratio());
+
+            // Show the new position in the syntax accepted by the command, allowing it to be typed again.
+            let seek_time =
+                TimeParser::format(u64::try_from(seek_result.position / 1000).unwrap_or_default());
 
             // Get the translation message.
             let translation_message = if let Some(uri) = seek_result.track.uri {
@@ -170,6 +206,7 @@ pub async fn execute(
                     .replace("{current}", &current_time)
                     .replace("{total}", &total_time)
                     .replace("{progress}", &progress_bar)
+                    .replace("{time}", &seek_time)
             } else {
                 hydrogen
                     .i18n
@@ -179,6 +216,7 @@ pub async fn execute(
                     .replace("{current}", &current_time)
                     .replace("{total}", &total_time)
                     .replace("{progress}", &progress_bar)
+                    .replace("{time}", &seek_time)
             };
 
             Ok(Response::Generic {
@@ -217,30 +255,24 @@ pub async fn execute(
 ///
 /// If `i18n` is `None`, the translation will be ignored.
 pub fn register(i18n: Option<&I18n>) -> CreateCommand {
-    let mut command = CreateCommand::new("seek");
-
-    if let Some(i18n) = i18n {
-        command = i18n.serenity_command_name("seek", "name", command);
-        command = i18n.serenity_command_description("seek", "description", command);
-    }
-
-    command
-        .description("Seek for the time in the current music playing.")
-        .add_option({
-            let mut option = CreateCommandOption::new(
-                CommandOptionType::String,
-                "time",
-                "Time in seconds or a supported syntax.",
-            )
-            .required(true);
-
-            if let Some(i18n) = i18n {
-                option = i18n.serenity_command_option_name("seek", "time_name", option);
-                option =
-                    i18n.serenity_command_option_description("seek", "time_description", option);
-            }
-
-            option
-        })
-        .dm_permission(false)
+    translate_command(
+        i18n,
+        "seek",
+        "name",
+        "description",
+        CreateCommand::new("seek"),
+    )
+    .description("Seek for the time in the current music playing.")
+    .add_option(translate_option(
+        i18n,
+        "seek",
+        "time_name",
+        "time_description",
+        CreateCommandOption::new(
+            CommandOptionType::String,
+            "time",
+            "Time in seconds or a supported syntax.",
+        )
+        .required(true),
+    ))
 }
@@ -0,0 +1,352 @@
+//! Hydrogen // Commands // Settings
+//!
+//! '/settings' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{
+    all::{CommandDataOptionValue, CommandInteraction, CommandOptionType, Permissions},
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+};
+use tracing::error;
+
+use crate::{
+    handler::{Response, Result},
+    player::{LoopType, SearchSource},
+    utils::{translate_command, translate_option, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL,
+};
+
+/// Executes the `/settings` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "settings", "embed_title");
+
+    // Get the subcommand and its option.
+    let Some((subcommand, option)) =
+        interaction
+            .data
+            .options
+            .first()
+            .and_then(|v| match &v.value {
+                CommandDataOptionValue::SubCommand(options) => options
+                    .first()
+                    .map(|option| (v.name.as_str(), &option.value)),
+                _ => None,
+            })
+    else {
+        error!("cannot get the '/settings' subcommand");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Change the guild's setting.
+    match (subcommand, option) {
+        ("search_source", CommandDataOptionValue::String(source)) => {
+            let Some(search_source) = SearchSource::from_name(source) else {
+                error!("unknown search source '{}'", source);
+
+                return Err(Response::Generic {
+                    title,
+                    description: hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "unknown")
+                        .replace("{url}", HYDROGEN_BUG_URL),
+                });
+            };
+
+            data.manager.set_search_source(data.guild_id, search_source);
+
+            Ok(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "settings", "search_source_changed")
+                    .replace(
+                        "{source}",
+                        &hydrogen.i18n.translate(
+                            &interaction.locale,
+                            "settings",
+                            &format!("source_{}", search_source.name()),
+                        ),
+                    ),
+            })
+        }
+        ("remove_on_leave", CommandDataOptionValue::Boolean(enabled)) => {
+            data.manager.set_remove_on_leave(data.guild_id, *enabled);
+
+            Ok(Response::Generic {
+                title,
+                description: hydrogen.i18n.translate(
+                    &interaction.locale,
+                    "settings",
+                    if *enabled {
+                        "remove_on_leave_enabled"
+                    } else {
+                        "remove_on_leave_disabled"
+                    },
+                ),
+            })
+        }
+        ("default_loop", CommandDataOptionValue::String(name)) => {
+            let Ok(loop_type) = name.parse::<LoopType>() else {
+                error!("unknown loop type '{}'", name);
+
+                return Err(Response::Generic {
+                    title,
+                    description: hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "unknown")
+                        .replace("{url}", HYDROGEN_BUG_URL),
+                });
+            };
+
+            let loop_name =
+                hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "loop", loop_type.translation_key());
+            data.manager.set_default_loop(data.guild_id, loop_type);
+
+            Ok(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "settings", "default_loop_changed")
+                    .replace("{loop}", &loop_name),
+            })
+        }
+        ("duplicates", CommandDataOptionValue::Boolean(enabled)) => {
+            data.manager.set_warn_duplicates(data.guild_id, *enabled);
+
+            Ok(Response::Generic {
+                title,
+                description: hydrogen.i18n.translate(
+                    &interaction.locale,
+                    "settings",
+                    if *enabled {
+                        "duplicates_enabled"
+                    } else {
+                        "duplicates_disabled"
+                    },
+                ),
+            })
+        }
+        ("roll_here", CommandDataOptionValue::Boolean(enabled)) => {
+            if *enabled {
+                hydrogen
+                    .disabled_roll_channels
+                    .remove_if_mut(&data.guild_id, |_, channels| {
+                        channels.remove(&interaction.channel_id);
+                        channels.is_empty()
+                    });
+            } else {
+                hydrogen
+                    .disabled_roll_channels
+                    .entry(data.guild_id)
+                    .or_default()
+                    .insert(interaction.channel_id);
+            }
+
+            Ok(Response::Generic {
+                title,
+                description: hydrogen.i18n.translate(
+                    &interaction.locale,
+                    "settings",
+                    if *enabled {
+                        "roll_here_enabled"
+                    } else {
+                        "roll_here_disabled"
+                    },
+                ),
+            })
+        }
+        _ => {
+            error!("unknown '/settings' subcommand: {}", subcommand);
+
+            Err(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            })
+        }
+    }
+}
+
+/// Registers the `/settings` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "settings",
+        "name",
+        "description",
+        CreateCommand::new("settings"),
+    )
+    .description("Change the settings of this server.")
+    .add_option(
+        translate_option(
+            i18n,
+            "settings",
+            "search_source_name",
+            "search_source_description",
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "search_source",
+                "Change where the songs are searched when the query isn't a URL.",
+            ),
+        )
+        .add_sub_option(
+            translate_option(
+                i18n,
+                "settings",
+                "source_name",
+                "source_description",
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "source",
+                    "The source used to search the songs.",
+                )
+                .required(true),
+            )
+            .add_string_choice("YouTube", SearchSource::YouTube.name())
+            .add_string_choice("YouTube Music", SearchSource::YouTubeMusic.name())
+            .add_string_choice("SoundCloud", SearchSource::SoundCloud.name()),
+        ),
+    )
+    .add_option(
+        translate_option(
+            i18n,
+            "settings",
+            "remove_on_leave_name",
+            "remove_on_leave_description",
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "remove_on_leave",
+                "Remove the songs of the users who leave the voice chat from the queue.",
+            ),
+        )
+        .add_sub_option(translate_option(
+            i18n,
+            "settings",
+            "enabled_name",
+            "enabled_description",
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "enabled",
+                "Whether the songs are removed.",
+            )
+            .required(true),
+        )),
+    )
+    .add_option(
+        translate_option(
+            i18n,
+            "settings",
+            "default_loop_name",
+            "default_loop_description",
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "default_loop",
+                "Change the loop mode used when the player is created.",
+            ),
+        )
+        .add_sub_option(
+            translate_option(
+                i18n,
+                "settings",
+                "loop_name",
+                "loop_description",
+                CreateCommandOption::new(
+                    CommandOptionType::String,
+                    "loop",
+                    "The loop mode used by the new players.",
+                )
+                .required(true),
+            )
+            .add_string_choice("Normal", LoopType::None.name())
+            .add_string_choice("Normal without auto-playing", LoopType::NoAutostart.name())
+            .add_string_choice("Repeat Song", LoopType::Music.name())
+            .add_string_choice("Repeat Queue", LoopType::Queue.name())
+            .add_string_choice("Next Random Song", LoopType::Random.name()),
+        ),
+    )
+    .add_option(
+        translate_option(
+            i18n,
+            "settings",
+            "duplicates_name",
+            "duplicates_description",
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "duplicates",
+                "Ask before adding a song that is already in the queue.",
+            ),
+        )
+        .add_sub_option(translate_option(
+            i18n,
+            "settings",
+            "enabled_name",
+            "duplicates_enabled_description",
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "enabled",
+                "Whether the duplicated songs need to be confirmed.",
+            )
+            .required(true),
+        )),
+    )
+    .add_option(
+        translate_option(
+            i18n,
+            "settings",
+            "roll_here_name",
+            "roll_here_description",
+            CreateCommandOption::new(
+                CommandOptionType::SubCommand,
+                "roll_here",
+                "Enable or disable the dice rolls from messages in this channel.",
+            ),
+        )
+        .add_sub_option(translate_option(
+            i18n,
+            "settings",
+            "enabled_name",
+            "roll_enabled_description",
+            CreateCommandOption::new(
+                CommandOptionType::Boolean,
+                "enabled",
+                "Whether the dice rolls are evaluated.",
+            )
+            .required(true),
+        )),
+    )
+    .default_member_permissions(Permissions::MANAGE_GUILD)
+}
@@ -0,0 +1,204 @@
+//! Hydrogen // Commands // Shuffle
+//!
+//! '/shuffle' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{
+    all::{CommandInteraction, CommandOptionType},
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+};
+use tracing::{error, warn};
+
+use crate::{
+    handler::{Response, Result},
+    utils::{error_message, translate_command, translate_option, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL,
+};
+
+/// Executes the `/shuffle` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "shuffle", "embed_title");
+
+    // Get the subcommand, 'on' shuffles the queue and 'off' restores its order.
+    let Some(enabled) = interaction
+        .data
+        .options
+        .first()
+        .and_then(|v| match v.name.as_str() {
+            "on" => Some(true),
+            "off" => Some(false),
+            _ => None,
+        })
+    else {
+        error!("cannot get the '/shuffle' subcommand");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the user's voice channel ID.
+    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+        warn!(
+            "cannot get the voice channel ID of the user {} in the guild {}",
+            interaction.user.id, data.guild_id
+        );
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown_voice_state")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            ),
+        });
+    };
+
+    // Get the player's voice channel ID.
+    let Some(my_channel_id) = data.manager.get_voice_channel_id(data.guild_id).await else {
+        // The player doesn't exists.
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "player_not_exists"),
+            ),
+        });
+    };
+
+    // Checks if the user is in the same voice channel as the bot.
+    if my_channel_id != voice_channel_id.into() {
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "not_in_voice_chat"),
+            ),
+        });
+    }
+
+    // Shuffle the queue or restore its order.
+    let result = if enabled {
+        data.manager.shuffle(data.guild_id).await
+    } else {
+        data.manager.unshuffle(data.guild_id).await
+    };
+
+    match result {
+        Ok(true) => Ok(Response::Generic {
+            title,
+            description: hydrogen.i18n.translate(
+                &interaction.locale,
+                "shuffle",
+                if enabled { "shuffled" } else { "restored" },
+            ),
+        }),
+        Ok(false) => {
+            warn!(
+                "cannot change the queue order in the guild {}",
+                data.guild_id
+            );
+
+            Err(Response::Generic {
+                title,
+                description: error_message(
+                    &hydrogen.i18n,
+                    &interaction.locale,
+                    &if enabled {
+                        hydrogen
+                            .i18n
+                            .translate(&interaction.locale, "error", "empty_queue")
+                    } else {
+                        hydrogen
+                            .i18n
+                            .translate(&interaction.locale, "shuffle", "not_shuffled")
+                    },
+                ),
+            })
+        }
+        Err(e) => {
+            error!(
+                "cannot change the queue order in the guild {}: {}",
+                data.guild_id, e
+            );
+
+            Err(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            })
+        }
+    }
+}
+
+/// Registers the `/shuffle` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "shuffle",
+        "name",
+        "description",
+        CreateCommand::new("shuffle"),
+    )
+    .description("Shuffle the queue or restore its order.")
+    .add_option(translate_option(
+        i18n,
+        "shuffle",
+        "on_name",
+        "on_description",
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "on",
+            "Shuffle the songs in the queue.",
+        ),
+    ))
+    .add_option(translate_option(
+        i18n,
+        "shuffle",
+        "off_name",
+        "off_description",
+        CreateCommandOption::new(
+            CommandOptionType::SubCommand,
+            "off",
+            "Restore the order of the queue before it was shuffled.",
+        ),
+    ))
+}
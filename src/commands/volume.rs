@@ -0,0 +1,163 @@
+//! Hydrogen // Commands // Volume
+//!
+//! '/volume' command registration and execution.
+
+use hydrogen_i18n::I18n;
+use serenity::{
+    all::{CommandInteraction, CommandOptionType},
+    builder::{CreateCommand, CreateCommandOption},
+    client::Context,
+};
+use tracing::{error, warn};
+
+use crate::{
+    handler::{Response, Result},
+    utils::{error_message, translate_command, translate_option, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL, HYDROGEN_MAX_VOLUME,
+};
+
+/// Executes the `/volume` command.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &CommandInteraction,
+) -> Result {
+    // Get the title of the embed.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "volume", "embed_title");
+
+    // Get the volume option value.
+    let Some(volume) = interaction
+        .data
+        .options
+        .first()
+        .and_then(|v| v.value.as_i64())
+        .and_then(|v| u16::try_from(v).ok())
+    else {
+        error!("cannot get the 'volume' option");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the user's voice channel ID.
+    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+        warn!(
+            "cannot get the voice channel ID of the user {} in the guild {}",
+            interaction.user.id, data.guild_id
+        );
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "unknown_voice_state")
+                    .replace("{url}", HYDROGEN_BUG_URL),
+            ),
+        });
+    };
+
+    // Get the player's voice channel ID.
+    if let Some(my_channel_id) = data.manager.get_voice_channel_id(data.guild_id).await {
+        // Checks if the user is in the same voice channel as the bot.
+        if my_channel_id == voice_channel_id.into() {
+            // Set the player's volume.
+            if let Err(e) = data.manager.set_volume(data.guild_id, volume).await {
+                error!(
+                    "cannot set the volume of the player in the guild {}: {}",
+                    data.guild_id, e
+                );
+
+                return Err(Response::Generic {
+                    title,
+                    description: hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "unknown")
+                        .replace("{url}", HYDROGEN_BUG_URL),
+                });
+            }
+
+            Ok(Response::Generic {
+                title,
+                description: hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "volume", "changed")
+                    .replace("{volume}", &volume.to_string()),
+            })
+        } else {
+            // The user is not in the same voice channel as the bot.
+            Err(Response::Generic {
+                title,
+                description: error_message(
+                    &hydrogen.i18n,
+                    &interaction.locale,
+                    &hydrogen
+                        .i18n
+                        .translate(&interaction.locale, "error", "not_in_voice_chat"),
+                ),
+            })
+        }
+    } else {
+        // The player doesn't exists.
+        Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "player_not_exists"),
+            ),
+        })
+    }
+}
+
+/// Registers the `/volume` command.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn register(i18n: Option<&I18n>) -> CreateCommand {
+    translate_command(
+        i18n,
+        "volume",
+        "name",
+        "description",
+        CreateCommand::new("volume"),
+    )
+    .description("Change the volume of the music player.")
+    .add_option(translate_option(
+        i18n,
+        "volume",
+        "volume_name",
+        "volume_description",
+        CreateCommandOption::new(
+            CommandOptionType::Integer,
+            "volume",
+            "The volume in percentage.",
+        )
+        .required(true)
+        .min_int_value(0)
+        .max_int_value(HYDROGEN_MAX_VOLUME.into()),
+    ))
+}
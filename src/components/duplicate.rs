@@ -0,0 +1,41 @@
+//! Hydrogen // Components // Duplicate
+//!
+//! 'duplicate_add' component execution, enqueueing a track already in the queue after the user confirms it.
+
+use serenity::{all::ComponentInteraction, client::Context};
+use tracing::warn;
+
+use crate::{
+    commands::play::play,
+    handler::{take_state, Response, Result},
+    HydrogenContext,
+};
+
+/// Executes the `duplicate_add` component.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &ComponentInteraction,
+) -> Result {
+    // Get the query waiting for the confirmation.
+    let Some(query) = take_state(
+        &hydrogen.pending_duplicates,
+        (interaction.channel_id, interaction.user.id),
+    ) else {
+        warn!(
+            "cannot find the duplicate query of the user {} in the channel {}",
+            interaction.user.id, interaction.channel_id
+        );
+
+        return Err(Response::Generic {
+            title: hydrogen
+                .i18n
+                .translate(&interaction.locale, "play", "embed_title"),
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "play", "duplicate_expired"),
+        });
+    };
+
+    play(hydrogen, context, &interaction.into(), &query, true).await
+}
@@ -76,20 +76,12 @@ pub async fn execute(
                 .set_loop_type(data.guild_id, new_loop_type.clone())
                 .await;
 
-            // Get the translation key for the new loop type.
-            let loop_type_translation_key = match new_loop_type {
-                LoopType::None => "autostart",
-                LoopType::NoAutostart => "no_autostart",
-                LoopType::Music => "music",
-                LoopType::Queue => "queue",
-                LoopType::Random => "random",
-            };
-
             // Get the translation for the new loop type.
-            let loop_type_translation =
-                hydrogen
-                    .i18n
-                    .translate(&interaction.locale, "loop", loop_type_translation_key);
+            let loop_type_translation = hydrogen.i18n.translate(
+                &interaction.locale,
+                "loop",
+                new_loop_type.translation_key(),
+            );
 
             Ok(Response::Generic {
                 title,
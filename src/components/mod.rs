@@ -2,8 +2,10 @@
 //!
 //! This module contains all the components from Hydrogen.
 
+pub mod duplicate;
 pub mod loop_switch;
 pub mod pause;
 pub mod prev;
+pub mod queue;
 pub mod skip;
 pub mod stop;
@@ -6,50 +6,56 @@ use serenity::{all::ComponentInteraction, client::Context};
 use tracing::{error, warn};
 
 use crate::{
-    handler::{Response, Result},
+    handler::{Invoker, Response, Result},
+    manager::PlaybackState,
     utils::{error_message, MusicCommonData},
     HydrogenContext, HYDROGEN_BUG_URL,
 };
 
-/// Executes the `pause` command.
+/// Executes the `pause` component.
 pub async fn execute(
     hydrogen: &HydrogenContext,
     context: &Context,
     interaction: &ComponentInteraction,
 ) -> Result {
+    run(hydrogen, context, &interaction.into()).await
+}
+
+/// Runs the `pause` action, used by the `pause` component and the prefix commands.
+pub async fn run(hydrogen: &HydrogenContext, context: &Context, invoker: &Invoker) -> Result {
     // Get the translation for the command's title.
     let title = hydrogen
         .i18n
-        .translate(&interaction.locale, "pause", "embed_title");
+        .translate(invoker.locale, "pause", "embed_title");
 
     // Get the common data used by music commands and components.
-    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+    let Some(data) = MusicCommonData::new(hydrogen, context, invoker.guild_id).await else {
         error!("cannot get common music data");
 
         return Err(Response::Generic {
             title,
             description: hydrogen
                 .i18n
-                .translate(&interaction.locale, "error", "unknown")
+                .translate(invoker.locale, "error", "unknown")
                 .replace("{url}", HYDROGEN_BUG_URL),
         });
     };
 
     // Get the user's voice channel ID.
-    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+    let Some(voice_channel_id) = data.get_connected_channel(invoker.user_id) else {
         warn!(
             "cannot get the voice channel ID of the user {} in the guild {}",
-            interaction.user.id, data.guild_id
+            invoker.user_id, data.guild_id
         );
 
         return Err(Response::Generic {
             title,
             description: error_message(
                 &hydrogen.i18n,
-                &interaction.locale,
+                invoker.locale,
                 &hydrogen
                     .i18n
-                    .translate(&interaction.locale, "error", "unknown_voice_state"),
+                    .translate(invoker.locale, "error", "unknown_voice_state"),
             ),
         });
     };
@@ -58,7 +64,24 @@ pub async fn execute(
     if let Some(my_channel_id) = data.manager.get_voice_channel_id(data.guild_id).await {
         if my_channel_id == voice_channel_id.into() {
             // Get the pause state and invert it.
-            let paused = !data.manager.get_paused(data.guild_id).await;
+            let paused = match data.manager.playback_state(data.guild_id).await {
+                PlaybackState::Playing => true,
+                PlaybackState::Paused => false,
+                PlaybackState::Idle | PlaybackState::NoPlayer => {
+                    warn!("guild {} doesn't have a track to pause", data.guild_id);
+
+                    return Err(Response::Generic {
+                        title,
+                        description: error_message(
+                            &hydrogen.i18n,
+                            invoker.locale,
+                            &hydrogen
+                                .i18n
+                                .translate(invoker.locale, "error", "empty_queue"),
+                        ),
+                    });
+                }
+            };
 
             // Pause or resume the player.
             if let Err(e) = data.manager.set_paused(data.guild_id, paused).await {
@@ -71,7 +94,7 @@ pub async fn execute(
                     title,
                     description: hydrogen
                         .i18n
-                        .translate(&interaction.locale, "error", "unknown")
+                        .translate(invoker.locale, "error", "unknown")
                         .replace("{url}", HYDROGEN_BUG_URL),
                 });
             }
@@ -83,7 +106,7 @@ pub async fn execute(
                 title,
                 description: hydrogen
                     .i18n
-                    .translate(&interaction.locale, "pause", translation_key),
+                    .translate(invoker.locale, "pause", translation_key),
             })
         } else {
             // Not in the same voice channel as the bot.
@@ -91,10 +114,10 @@ pub async fn execute(
                 title,
                 description: error_message(
                     &hydrogen.i18n,
-                    &interaction.locale,
+                    invoker.locale,
                     &hydrogen
                         .i18n
-                        .translate(&interaction.locale, "error", "not_in_voice_chat"),
+                        .translate(invoker.locale, "error", "not_in_voice_chat"),
                 ),
             })
         }
@@ -104,10 +127,10 @@ pub async fn execute(
             title,
             description: error_message(
                 &hydrogen.i18n,
-                &interaction.locale,
+                invoker.locale,
                 &hydrogen
                     .i18n
-                    .translate(&interaction.locale, "error", "player_not_exists"),
+                    .translate(invoker.locale, "error", "player_not_exists"),
             ),
         })
     }
@@ -0,0 +1,164 @@
+//! Hydrogen // Components // Queue
+//!
+//! 'queue', 'queue_prev' and 'queue_next' components execution.
+
+use serenity::{all::ComponentInteraction, client::Context};
+use tracing::{error, warn};
+
+use crate::{
+    handler::{get_page, set_page, Response, Result},
+    parsers::TimeParser,
+    player::{HydrogenMusic, HydrogenQueueDuration},
+    utils::{error_message, MusicCommonData},
+    HydrogenContext, HYDROGEN_BUG_URL, HYDROGEN_QUEUE_PAGE_SIZE,
+};
+
+/// Executes the `queue` component, also handling the page buttons.
+pub async fn execute(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    interaction: &ComponentInteraction,
+) -> Result {
+    // Get the translation for the command's title.
+    let title = hydrogen
+        .i18n
+        .translate(&interaction.locale, "queue", "embed_title");
+
+    // Get the common data used by music commands and components.
+    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+        error!("cannot get common music data");
+
+        return Err(Response::Generic {
+            title,
+            description: hydrogen
+                .i18n
+                .translate(&interaction.locale, "error", "unknown")
+                .replace("{url}", HYDROGEN_BUG_URL),
+        });
+    };
+
+    // Get the queue.
+    let Some((queue, index)) = data.manager.get_queue(data.guild_id).await else {
+        // Player doesn't exist.
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "player_not_exists"),
+            ),
+        });
+    };
+
+    if queue.is_empty() {
+        warn!("guild {} has a empty queue", data.guild_id);
+
+        return Err(Response::Generic {
+            title,
+            description: error_message(
+                &hydrogen.i18n,
+                &interaction.locale,
+                &hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "error", "empty_queue"),
+            ),
+        });
+    }
+
+    // Calculate the page to show, opening the queue at the page of the current track.
+    let pages = queue.len().div_ceil(HYDROGEN_QUEUE_PAGE_SIZE);
+    let page_key = (interaction.channel_id, interaction.user.id);
+    let page = match interaction.data.custom_id.as_str() {
+        "queue_prev" => get_page(&hydrogen.queue_pages, page_key).saturating_sub(1),
+        "queue_next" => get_page(&hydrogen.queue_pages, page_key) + 1,
+        _ => index / HYDROGEN_QUEUE_PAGE_SIZE,
+    }
+    .min(pages - 1);
+
+    set_page(&hydrogen.queue_pages, page_key, page);
+
+    // Get the total length of the queue.
+    let duration = data
+        .manager
+        .queue_duration(data.guild_id)
+        .await
+        .unwrap_or_default();
+
+    Ok(Response::Paginated {
+        title,
+        description: get_message(&queue, index, page, pages, duration, hydrogen, interaction),
+        id: "queue",
+        page,
+        pages,
+    })
+}
+
+/// Get the message to send to the user.
+fn get_message(
+    queue: &[HydrogenMusic],
+    index: usize,
+    page: usize,
+    pages: usize,
+    duration: HydrogenQueueDuration,
+    hydrogen: &HydrogenContext,
+    interaction: &ComponentInteraction,
+) -> String {
+    let start = page * HYDROGEN_QUEUE_PAGE_SIZE;
+
+    let mut tracks = queue
+        .iter()
+        .enumerate()
+        .skip(start)
+        .take(HYDROGEN_QUEUE_PAGE_SIZE)
+        .map(|(position, track)| {
+            let line = if let Some(uri) = &track.uri {
+                hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "queue", "track_url")
+                    .replace("{url}", uri)
+            } else {
+                hydrogen
+                    .i18n
+                    .translate(&interaction.locale, "queue", "track")
+            }
+            .replace("{position}", &(position + 1).to_string())
+            .replace("{name}", &track.title)
+            .replace("{author}", &track.author);
+
+            // Highlight the current track.
+            if position == index {
+                format!("**{}**", line)
+            } else {
+                line
+            }
+        })
+        .collect::<Vec<_>>();
+
+    tracks.push(String::new());
+    tracks.push(
+        hydrogen
+            .i18n
+            .translate(
+                &interaction.locale,
+                "queue",
+                if duration.streams > 0 {
+                    "total_streams"
+                } else {
+                    "total"
+                },
+            )
+            .replace("{duration}", &TimeParser::format(duration.total.as_secs()))
+            .replace("{streams}", &duration.streams.to_string()),
+    );
+    tracks.push(
+        hydrogen
+            .i18n
+            .translate(&interaction.locale, "queue", "page")
+            .replace("{page}", &(page + 1).to_string())
+            .replace("{pages}", &pages.to_string()),
+    );
+
+    tracks.join("\n")
+}
@@ -6,51 +6,56 @@ use serenity::{all::ComponentInteraction, client::Context};
 use tracing::{error, warn};
 
 use crate::{
-    handler::{Response, Result},
+    handler::{Invoker, Response, Result},
     player::HydrogenMusic,
     utils::{error_message, MusicCommonData},
     HydrogenContext, HYDROGEN_BUG_URL,
 };
 
-/// Executes the `skip` command.
+/// Executes the `skip` component.
 pub async fn execute(
     hydrogen: &HydrogenContext,
     context: &Context,
     interaction: &ComponentInteraction,
 ) -> Result {
+    run(hydrogen, context, &interaction.into()).await
+}
+
+/// Runs the `skip` action, used by the `skip` component and the prefix commands.
+pub async fn run(hydrogen: &HydrogenContext, context: &Context, invoker: &Invoker) -> Result {
     // Get the translation for the command's title.
     let title = hydrogen
         .i18n
-        .translate(&interaction.locale, "skip", "embed_title");
+        .translate(invoker.locale, "skip", "embed_title");
 
     // Get the common data used by music commands and components.
-    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+    let Some(data) = MusicCommonData::new(hydrogen, context, invoker.guild_id).await else {
         error!("cannot get common music data");
 
         return Err(Response::Generic {
             title,
             description: hydrogen
                 .i18n
-                .translate(&interaction.locale, "error", "unknown")
+                .translate(invoker.locale, "error", "unknown")
                 .replace("{url}", HYDROGEN_BUG_URL),
         });
     };
 
     // Get the user's voice channel ID.
-    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+    let Some(voice_channel_id) = data.get_connected_channel(invoker.user_id) else {
         warn!(
             "cannot get the voice channel ID of the user {} in the guild {}",
-            interaction.user.id, data.guild_id
+            invoker.user_id, data.guild_id
         );
 
         return Err(Response::Generic {
             title,
             description: error_message(
                 &hydrogen.i18n,
-                &interaction.locale,
+                invoker.locale,
                 &hydrogen
                     .i18n
-                    .translate(&interaction.locale, "error", "unknown_voice_state"),
+                    .translate(invoker.locale, "error", "unknown_voice_state"),
             ),
         });
     };
@@ -71,7 +76,7 @@ pub async fn execute(
                         title,
                         description: hydrogen
                             .i18n
-                            .translate(&interaction.locale, "error", "unknown")
+                            .translate(invoker.locale, "error", "unknown")
                             .replace("{url}", HYDROGEN_BUG_URL),
                     });
                 }
@@ -85,17 +90,17 @@ pub async fn execute(
                     title,
                     description: error_message(
                         &hydrogen.i18n,
-                        &interaction.locale,
+                        invoker.locale,
                         &hydrogen
                             .i18n
-                            .translate(&interaction.locale, "error", "empty_queue"),
+                            .translate(invoker.locale, "error", "empty_queue"),
                     ),
                 });
             };
 
             Ok(Response::Generic {
                 title,
-                description: get_message(music, hydrogen, interaction),
+                description: get_message(music, hydrogen, invoker),
             })
         } else {
             // Not in the same voice channel as the bot.
@@ -103,10 +108,10 @@ pub async fn execute(
                 title,
                 description: error_message(
                     &hydrogen.i18n,
-                    &interaction.locale,
+                    invoker.locale,
                     &hydrogen
                         .i18n
-                        .translate(&interaction.locale, "error", "not_in_voice_chat"),
+                        .translate(invoker.locale, "error", "not_in_voice_chat"),
                 ),
             })
         }
@@ -116,32 +121,28 @@ pub async fn execute(
             title,
             description: error_message(
                 &hydrogen.i18n,
-                &interaction.locale,
+                invoker.locale,
                 &hydrogen
                     .i18n
-                    .translate(&interaction.locale, "error", "player_not_exists"),
+                    .translate(invoker.locale, "error", "player_not_exists"),
             ),
         })
     }
 }
 
 /// Get the message to send to the user.
-fn get_message(
-    track: HydrogenMusic,
-    hydrogen: &HydrogenContext,
-    interaction: &ComponentInteraction,
-) -> String {
+fn get_message(track: HydrogenMusic, hydrogen: &HydrogenContext, invoker: &Invoker) -> String {
     if let Some(uri) = track.uri {
         hydrogen
             .i18n
-            .translate(&interaction.locale, "skip", "skipping_url")
+            .translate(invoker.locale, "skip", "skipping_url")
             .replace("{name}", &track.title)
             .replace("{author}", &track.author)
             .replace("{url}", &uri)
     } else {
         hydrogen
             .i18n
-            .translate(&interaction.locale, "skip", "skipping")
+            .translate(invoker.locale, "skip", "skipping")
             .replace("{name}", &track.title)
             .replace("{author}", &track.author)
     }
@@ -6,50 +6,55 @@ use serenity::{all::ComponentInteraction, client::Context};
 use tracing::{error, warn};
 
 use crate::{
-    handler::{Response, Result},
+    handler::{Invoker, Response, Result},
     utils::{error_message, MusicCommonData},
     HydrogenContext, HYDROGEN_BUG_URL,
 };
 
-/// Executes the `stop` command.
+/// Executes the `stop` component.
 pub async fn execute(
     hydrogen: &HydrogenContext,
     context: &Context,
     interaction: &ComponentInteraction,
 ) -> Result {
+    run(hydrogen, context, &interaction.into()).await
+}
+
+/// Runs the `stop` action, used by the `stop` component and the prefix commands.
+pub async fn run(hydrogen: &HydrogenContext, context: &Context, invoker: &Invoker) -> Result {
     // Get the translation for the command's title.
     let title = hydrogen
         .i18n
-        .translate(&interaction.locale, "stop", "embed_title");
+        .translate(invoker.locale, "stop", "embed_title");
 
     // Get the common data used by music commands and components.
-    let Some(data) = MusicCommonData::new(hydrogen, context, interaction.guild_id).await else {
+    let Some(data) = MusicCommonData::new(hydrogen, context, invoker.guild_id).await else {
         error!("cannot get common music data");
 
         return Err(Response::Generic {
             title,
             description: hydrogen
                 .i18n
-                .translate(&interaction.locale, "error", "unknown")
+                .translate(invoker.locale, "error", "unknown")
                 .replace("{url}", HYDROGEN_BUG_URL),
         });
     };
 
     // Get the user's voice channel ID.
-    let Some(voice_channel_id) = data.get_connected_channel(interaction.user.id) else {
+    let Some(voice_channel_id) = data.get_connected_channel(invoker.user_id) else {
         warn!(
             "cannot get the voice channel ID of the user {} in the guild {}",
-            interaction.user.id, data.guild_id
+            invoker.user_id, data.guild_id
         );
 
         return Err(Response::Generic {
             title,
             description: error_message(
                 &hydrogen.i18n,
-                &interaction.locale,
+                invoker.locale,
                 &hydrogen
                     .i18n
-                    .translate(&interaction.locale, "error", "unknown_voice_state"),
+                    .translate(invoker.locale, "error", "unknown_voice_state"),
             ),
         });
     };
@@ -68,19 +73,18 @@ pub async fn execute(
                     title,
                     description: error_message(
                         &hydrogen.i18n,
-                        &interaction.locale,
-                        &hydrogen
-                            .i18n
-                            .translate(&interaction.locale, "error", "unknown"),
+                        invoker.locale,
+                        &hydrogen.i18n.translate(invoker.locale, "error", "unknown"),
                     ),
                 });
             }
 
+            // Forget the volume, as the user explicitly stopped the player.
+            data.manager.reset_volume(data.guild_id);
+
             Ok(Response::Generic {
                 title,
-                description: hydrogen
-                    .i18n
-                    .translate(&interaction.locale, "stop", "stopped"),
+                description: hydrogen.i18n.translate(invoker.locale, "stop", "stopped"),
             })
         } else {
             // Not in the same voice channel as the bot.
@@ -88,10 +92,10 @@ pub async fn execute(
                 title,
                 description: error_message(
                     &hydrogen.i18n,
-                    &interaction.locale,
+                    invoker.locale,
                     &hydrogen
                         .i18n
-                        .translate(&interaction.locale, "error", "not_in_voice_chat"),
+                        .translate(invoker.locale, "error", "not_in_voice_chat"),
                 ),
             })
         }
@@ -101,10 +105,10 @@ pub async fn execute(
             title,
             description: error_message(
                 &hydrogen.i18n,
-                &interaction.locale,
+                invoker.locale,
                 &hydrogen
                     .i18n
-                    .translate(&interaction.locale, "error", "player_not_exists"),
+                    .translate(invoker.locale, "error", "player_not_exists"),
             ),
         })
     }
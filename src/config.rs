@@ -2,7 +2,7 @@
 
 use std::{
     env, error,
-    fmt::{self, Display, Formatter},
+    fmt::{self, Debug, Display, Formatter},
     fs::read_to_string,
     io,
     path::{Path, PathBuf},
@@ -50,7 +50,7 @@ mod unix {
 #[cfg(unix)]
 pub use unix::*;
 
-use crate::lavalink::LavalinkNodeInfo;
+use crate::lavalink::{LavalinkNodeInfo, REDACTED};
 
 /// The command line arguments.
 #[derive(Debug, Parser, PartialEq, Eq, Clone)]
@@ -92,8 +92,23 @@ fn default_lavalink_password() -> String {
     "youshallnotpass".to_owned()
 }
 
+/// The default interval in seconds between the keep-alive pings sent to Lavalink.
+fn default_lavalink_ping_interval() -> u64 {
+    30
+}
+
+/// The default amount of times to try reconnecting to Lavalink after the connection drops.
+fn default_lavalink_reconnect_attempts() -> u32 {
+    5
+}
+
+/// The default seconds Lavalink keeps the session after the connection drops, allowing it to be resumed.
+fn default_lavalink_resume_timeout() -> u32 {
+    60
+}
+
 /// Configuration for a single Lavalink node.
-#[derive(Debug, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Clone, Deserialize, PartialEq, Eq)]
 pub struct LavalinkConfig {
     /// The address of the Lavalink server.
     #[serde(default = "default_lavalink_address")]
@@ -104,6 +119,77 @@ pub struct LavalinkConfig {
     /// Whether to use TLS to connect to the Lavalink server.
     #[serde(default)]
     pub tls: bool,
+    /// Interval in seconds between the keep-alive pings, `0` disables them.
+    #[serde(default = "default_lavalink_ping_interval")]
+    pub ping_interval: u64,
+    /// Path where the Lavalink API is served, used behind reverse proxies.
+    #[serde(default)]
+    pub path_prefix: Option<String>,
+    /// Times to try reconnecting after the connection drops, `0` disables it.
+    #[serde(default = "default_lavalink_reconnect_attempts")]
+    pub reconnect_attempts: u32,
+    /// Seconds Lavalink keeps the session after the connection drops, resuming it when reconnecting, `0` disables it.
+    #[serde(default = "default_lavalink_resume_timeout")]
+    pub resume_timeout: u32,
+}
+
+impl Debug for LavalinkConfig {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("LavalinkConfig")
+            .field("address", &self.address)
+            .field("password", &REDACTED)
+            .field("tls", &self.tls)
+            .field("ping_interval", &self.ping_interval)
+            .field("path_prefix", &self.path_prefix)
+            .field("reconnect_attempts", &self.reconnect_attempts)
+            .field("resume_timeout", &self.resume_timeout)
+            .finish()
+    }
+}
+
+impl LavalinkConfig {
+    /// Creates a new Lavalink node configuration without TLS.
+    pub fn new(address: impl Into<String>, password: impl Into<String>) -> Self {
+        Self {
+            address: address.into(),
+            password: password.into(),
+            tls: false,
+            ping_interval: default_lavalink_ping_interval(),
+            path_prefix: None,
+            reconnect_attempts: default_lavalink_reconnect_attempts(),
+            resume_timeout: default_lavalink_resume_timeout(),
+        }
+    }
+
+    /// Sets whether to use TLS to connect to the Lavalink server.
+    pub fn with_tls(mut self, tls: bool) -> Self {
+        self.tls = tls;
+        self
+    }
+
+    /// Sets the interval in seconds between the keep-alive pings, `0` disables them.
+    pub fn with_ping_interval(mut self, ping_interval: u64) -> Self {
+        self.ping_interval = ping_interval;
+        self
+    }
+
+    /// Sets the path where the Lavalink API is served, used behind reverse proxies.
+    pub fn with_path_prefix(mut self, path_prefix: Option<String>) -> Self {
+        self.path_prefix = path_prefix;
+        self
+    }
+
+    /// Sets the times to try reconnecting after the connection drops, `0` disables it.
+    pub fn with_reconnect_attempts(mut self, reconnect_attempts: u32) -> Self {
+        self.reconnect_attempts = reconnect_attempts;
+        self
+    }
+
+    /// Sets the seconds Lavalink keeps the session after the connection drops, `0` disables it.
+    pub fn with_resume_timeout(mut self, resume_timeout: u32) -> Self {
+        self.resume_timeout = resume_timeout;
+        self
+    }
 }
 
 impl From<&str> for LavalinkConfig {
@@ -129,11 +215,36 @@ impl From<&str> for LavalinkConfig {
             .map(|s| matches!(s.to_lowercase().as_str(), "true" | "yes" | "1" | "enabled"))
             .unwrap_or(false);
 
-        Self {
-            address,
-            password,
-            tls,
-        }
+        // Get the keep-alive ping interval.
+        let ping_interval = components
+            .next()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default_lavalink_ping_interval());
+
+        // Get the path prefix.
+        let path_prefix = components
+            .next()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_owned());
+
+        // Get the reconnection attempts.
+        let reconnect_attempts = components
+            .next()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default_lavalink_reconnect_attempts());
+
+        // Get the session resuming timeout.
+        let resume_timeout = components
+            .next()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(default_lavalink_resume_timeout());
+
+        Self::new(address, password)
+            .with_tls(tls)
+            .with_ping_interval(ping_interval)
+            .with_path_prefix(path_prefix)
+            .with_reconnect_attempts(reconnect_attempts)
+            .with_resume_timeout(resume_timeout)
     }
 }
 
@@ -143,12 +254,16 @@ impl From<LavalinkConfig> for LavalinkNodeInfo {
             host: config.address,
             password: config.password,
             tls: config.tls,
+            ping_interval: config.ping_interval,
+            path_prefix: config.path_prefix,
+            reconnect_attempts: config.reconnect_attempts,
+            resume_timeout: config.resume_timeout,
         }
     }
 }
 
 /// The configuration of the server.
-#[derive(Debug, Default, Clone, Deserialize, PartialEq, Eq)]
+#[derive(Default, Clone, Deserialize, PartialEq, Eq)]
 pub struct Config {
     /// The default language of the server.
     pub default_language: Option<String>,
@@ -162,6 +277,76 @@ pub struct Config {
     pub public_instance: Option<bool>,
     /// If the bot should force enable auto-roll from messages.
     pub force_roll: Option<bool>,
+    /// If the commands should sometimes show that the instance is ending, only used by the official instance.
+    pub show_sunset_notice: Option<bool>,
+    /// When the instance ends, as a UNIX timestamp in seconds, shown by the sunset notice.
+    pub sunset_time: Option<u64>,
+    /// The cooldown in milliseconds between components used by the same user.
+    pub component_cooldown: Option<u64>,
+    /// If the command responses should be deleted after some time.
+    pub auto_delete_responses: Option<bool>,
+    /// How long in seconds the command responses are kept before being deleted.
+    pub response_delete_delay: Option<u64>,
+    /// The color used by the embeds, in hexadecimal like `#5865f2`.
+    pub primary_color: Option<String>,
+    /// The color used by the error embeds, in hexadecimal like `#f04747`.
+    pub error_color: Option<String>,
+    /// Maximum amount of loaded tracks kept in the cache, `0` disables it.
+    pub track_cache_size: Option<usize>,
+    /// How long in seconds the loaded tracks are kept in the cache.
+    pub track_cache_ttl: Option<u64>,
+    /// The address where the readiness endpoint listens, like `0.0.0.0:8080`.
+    pub health_address: Option<String>,
+    /// The token required to read the players' snapshot from the readiness endpoint, disabled if not set.
+    pub health_token: Option<String>,
+    /// How long in seconds a player can stay paused before leaving, disabled if not set.
+    pub auto_leave_paused_secs: Option<u64>,
+    /// The prefix of the text commands, like `!`, disabled if not set.
+    pub command_prefix: Option<String>,
+    /// What the player does when it's alone in the voice channel, `leave` or `pause`.
+    pub idle_action: Option<String>,
+    /// Maximum of tracks in the queue requested by the same user, disabled if not set.
+    pub user_queue_limit: Option<usize>,
+    /// Maximum of players in each Lavalink node, disabled if not set.
+    pub max_players_per_node: Option<usize>,
+    /// Maximum CPU load in percent of each Lavalink node, disabled if not set.
+    pub max_node_cpu_load: Option<u8>,
+}
+
+impl Debug for Config {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.debug_struct("Config")
+            .field("default_language", &self.default_language)
+            .field("language_path", &self.language_path)
+            .field("lavalink", &self.lavalink)
+            .field(
+                "discord_token",
+                &self.discord_token.as_ref().map(|_| REDACTED),
+            )
+            .field("public_instance", &self.public_instance)
+            .field("force_roll", &self.force_roll)
+            .field("show_sunset_notice", &self.show_sunset_notice)
+            .field("sunset_time", &self.sunset_time)
+            .field("component_cooldown", &self.component_cooldown)
+            .field("auto_delete_responses", &self.auto_delete_responses)
+            .field("response_delete_delay", &self.response_delete_delay)
+            .field("primary_color", &self.primary_color)
+            .field("error_color", &self.error_color)
+            .field("track_cache_size", &self.track_cache_size)
+            .field("track_cache_ttl", &self.track_cache_ttl)
+            .field("health_address", &self.health_address)
+            .field(
+                "health_token",
+                &self.health_token.as_ref().map(|_| REDACTED),
+            )
+            .field("auto_leave_paused_secs", &self.auto_leave_paused_secs)
+            .field("command_prefix", &self.command_prefix)
+            .field("idle_action", &self.idle_action)
+            .field("user_queue_limit", &self.user_queue_limit)
+            .field("max_players_per_node", &self.max_players_per_node)
+            .field("max_node_cpu_load", &self.max_node_cpu_load)
+            .finish()
+    }
 }
 
 impl Config {
@@ -209,6 +394,113 @@ impl Config {
                 .map(|s| matches!(s.to_lowercase().as_str(), "true" | "yes" | "1" | "enabled"))
         });
 
+        // Get the sunset notice from the environment.
+        let show_sunset_notice = self.show_sunset_notice.or_else(|| {
+            env::var("HYDROGEN_SHOW_SUNSET_NOTICE")
+                .ok()
+                .map(|s| matches!(s.to_lowercase().as_str(), "true" | "yes" | "1" | "enabled"))
+        });
+
+        // Get the sunset time from the environment.
+        let sunset_time = self.sunset_time.or_else(|| {
+            env::var("HYDROGEN_SUNSET_TIME")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
+        // Get the component cooldown from the environment.
+        let component_cooldown = self.component_cooldown.or_else(|| {
+            env::var("HYDROGEN_COMPONENT_COOLDOWN")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
+        // Get the command responses' auto-delete from the environment.
+        let auto_delete_responses = self.auto_delete_responses.or_else(|| {
+            env::var("HYDROGEN_AUTO_DELETE_RESPONSES")
+                .ok()
+                .map(|s| matches!(s.to_lowercase().as_str(), "true" | "yes" | "1" | "enabled"))
+        });
+
+        // Get the command responses' delete delay from the environment.
+        let response_delete_delay = self.response_delete_delay.or_else(|| {
+            env::var("HYDROGEN_RESPONSE_DELETE_DELAY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
+        // Get the primary color from the environment.
+        let primary_color = self
+            .primary_color
+            .or_else(|| env::var("HYDROGEN_PRIMARY_COLOR").ok());
+
+        // Get the error color from the environment.
+        let error_color = self
+            .error_color
+            .or_else(|| env::var("HYDROGEN_ERROR_COLOR").ok());
+
+        // Get the track cache size from the environment.
+        let track_cache_size = self.track_cache_size.or_else(|| {
+            env::var("HYDROGEN_TRACK_CACHE_SIZE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
+        // Get the track cache TTL from the environment.
+        let track_cache_ttl = self.track_cache_ttl.or_else(|| {
+            env::var("HYDROGEN_TRACK_CACHE_TTL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
+        // Get the readiness endpoint address from the environment.
+        let health_address = self
+            .health_address
+            .or_else(|| env::var("HYDROGEN_HEALTH_ADDRESS").ok());
+
+        // Get the players' snapshot token from the environment.
+        let health_token = self
+            .health_token
+            .or_else(|| env::var("HYDROGEN_HEALTH_TOKEN").ok());
+
+        // Get the paused player timeout from the environment.
+        let auto_leave_paused_secs = self.auto_leave_paused_secs.or_else(|| {
+            env::var("HYDROGEN_AUTO_LEAVE_PAUSED_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
+        // Get the text commands' prefix from the environment.
+        let command_prefix = self
+            .command_prefix
+            .or_else(|| env::var("HYDROGEN_COMMAND_PREFIX").ok());
+
+        // Get the idle action from the environment.
+        let idle_action = self
+            .idle_action
+            .or_else(|| env::var("HYDROGEN_IDLE_ACTION").ok());
+
+        // Get the queue limit of each user from the environment.
+        let user_queue_limit = self.user_queue_limit.or_else(|| {
+            env::var("HYDROGEN_USER_QUEUE_LIMIT")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
+        // Get the players limit of each Lavalink node from the environment.
+        let max_players_per_node = self.max_players_per_node.or_else(|| {
+            env::var("HYDROGEN_MAX_PLAYERS_PER_NODE")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
+        // Get the CPU load limit of each Lavalink node from the environment.
+        let max_node_cpu_load = self.max_node_cpu_load.or_else(|| {
+            env::var("HYDROGEN_MAX_NODE_CPU_LOAD")
+                .ok()
+                .and_then(|s| s.parse().ok())
+        });
+
         Self {
             default_language,
             language_path,
@@ -216,8 +508,86 @@ impl Config {
             discord_token,
             public_instance,
             force_roll,
+            show_sunset_notice,
+            sunset_time,
+            component_cooldown,
+            auto_delete_responses,
+            response_delete_delay,
+            primary_color,
+            error_color,
+            track_cache_size,
+            track_cache_ttl,
+            health_address,
+            health_token,
+            auto_leave_paused_secs,
+            command_prefix,
+            idle_action,
+            user_queue_limit,
+            max_players_per_node,
+            max_node_cpu_load,
+        }
+    }
+
+    /// Checks the required values, returning every problem found instead of only the first one.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+
+        if self
+            .discord_token
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .is_empty()
+        {
+            errors.push(
+                "the Discord token is missing, set 'discord_token' in the config file or HYDROGEN_DISCORD_TOKEN".to_owned(),
+            );
         }
+
+        match self.lavalink.as_deref() {
+            None | Some([]) => errors.push(
+                "no Lavalink node is configured, add a '[[lavalink]]' section to the config file or set HYDROGEN_LAVALINK".to_owned(),
+            ),
+            Some(nodes) => {
+                for (index, node) in nodes.iter().enumerate() {
+                    if !is_valid_address(&node.address) {
+                        errors.push(format!(
+                            "the address '{}' of the Lavalink node {} isn't in the 'host:port' format",
+                            node.address,
+                            index + 1
+                        ));
+                    }
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Checks if the address is in the `host:port` format.
+fn is_valid_address(address: &str) -> bool {
+    address
+        .rsplit_once(':')
+        .is_some_and(|(host, port)| !host.is_empty() && port.parse::<u16>().is_ok())
+}
+
+/// Parse a hexadecimal color like `#5865f2` into the value used by the embeds.
+pub fn parse_color(color: &str) -> Option<i32> {
+    let hex = color
+        .strip_prefix('#')
+        .or_else(|| color.strip_prefix("0x"))
+        .unwrap_or(color);
+
+    if hex.len() != 6 {
+        return None;
     }
+
+    i32::from_str_radix(hex, 16).ok()
 }
 
 /// Try to load the configuration file.
@@ -239,3 +609,44 @@ pub fn load_configuration() -> Config {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_reports_every_problem() {
+        let errors = Config::default().validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn validate_checks_the_node_addresses() {
+        let config = Config {
+            discord_token: Some("token".to_owned()),
+            lavalink: Some(vec![
+                LavalinkConfig::new("127.0.0.1:2333", "youshallnotpass"),
+                LavalinkConfig::new("lavalink", "youshallnotpass"),
+            ]),
+            ..Default::default()
+        };
+
+        let errors = config.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("node 2"));
+    }
+
+    #[test]
+    fn valid_config() {
+        let config = Config {
+            discord_token: Some("token".to_owned()),
+            lavalink: Some(vec![LavalinkConfig::new(
+                "lavalink:2333",
+                "youshallnotpass",
+            )]),
+            ..Default::default()
+        };
+
+        assert!(config.validate().is_ok());
+    }
+}
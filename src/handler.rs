@@ -6,18 +6,21 @@ use std::{
     collections::HashMap,
     result,
     sync::Arc,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
-use dashmap::DashMap;
+use dashmap::{mapref::entry::Entry, DashMap};
 use hydrogen_i18n::I18n;
 use rand::{thread_rng, Rng};
 use serenity::{
     all::{
-        ChannelId, Command, CommandId, CommandInteraction, ComponentInteraction,
-        CreateInteractionResponse, CreateInteractionResponseMessage, UserId,
+        ButtonStyle, ChannelId, Command, CommandId, CommandInteraction, ComponentInteraction,
+        CreateInteractionResponse, CreateInteractionResponseMessage, GuildId, Message, UserId,
+    },
+    builder::{
+        CreateActionRow, CreateButton, CreateEmbed, CreateEmbedFooter, CreateMessage,
+        EditInteractionResponse,
     },
-    builder::{CreateEmbed, CreateEmbedFooter, EditInteractionResponse},
     client::Context,
     http::{CacheHttp, Http},
 };
@@ -25,9 +28,9 @@ use tokio::{spawn, sync::RwLock, task::JoinHandle, time::sleep};
 use tracing::{debug, error, info, warn};
 
 use crate::{
-    commands, components, HydrogenContext, HYDROGEN_COLOR, HYDROGEN_ERROR_COLOR, HYDROGEN_LOGO_URL,
-    HYDROGEN_PRIMARY_COLOR, HYDROGEN_REPOSITORY_URL, HYDROGEN_WARNING_PROBABILITY,
-    HYDROGEN_WARNING_TIMEOUT,
+    commands, components, HydrogenContext, HYDROGEN_COLOR, HYDROGEN_COOLDOWN_SWEEP_INTERVAL,
+    HYDROGEN_LOGO_URL, HYDROGEN_PAGE_STATE_LIMIT, HYDROGEN_PAGE_STATE_TIMEOUT,
+    HYDROGEN_REPOSITORY_URL, HYDROGEN_WARNING_PROBABILITY, HYDROGEN_WARNING_TIMEOUT,
 };
 
 /// Type returned by commands and components to indicate how to respond to the interaction.
@@ -40,24 +43,189 @@ pub enum Response {
         /// Embed's description.
         description: String,
     },
+
+    /// Paginated response, adding buttons to change the page.
+    Paginated {
+        /// Embed's title.
+        title: String,
+
+        /// Embed's description.
+        description: String,
+
+        /// Prefix of the buttons' custom IDs, suffixed by `_prev` and `_next`.
+        id: &'static str,
+
+        /// Current page, starting at zero.
+        page: usize,
+
+        /// Total of pages.
+        pages: usize,
+    },
+
+    /// Confirmation response, adding a button to confirm the action.
+    Confirm {
+        /// Embed's title.
+        title: String,
+
+        /// Embed's description.
+        description: String,
+
+        /// Custom ID of the button.
+        id: &'static str,
+
+        /// Label of the button.
+        label: String,
+    },
+
+    /// Track response, showing the track's artwork as the thumbnail.
+    Track {
+        /// Embed's title.
+        title: String,
+
+        /// Embed's description.
+        description: String,
+
+        /// URL of the track's artwork.
+        thumbnail: String,
+    },
 }
 
 /// Command' and component's function return type.
 pub type Result = result::Result<Response, Response>;
 
+/// Who is executing a command or component and where, allowing them to be executed by prefix commands too.
+pub struct Invoker<'a> {
+    pub guild_id: Option<GuildId>,
+    pub channel_id: ChannelId,
+    pub user_id: UserId,
+    /// The user's locale, used by the responses.
+    pub locale: &'a str,
+    /// The guild's locale, used by the player messages.
+    pub guild_locale: &'a str,
+}
+
+impl<'a> From<&'a CommandInteraction> for Invoker<'a> {
+    fn from(value: &'a CommandInteraction) -> Self {
+        Self {
+            guild_id: value.guild_id,
+            channel_id: value.channel_id,
+            user_id: value.user.id,
+            locale: &value.locale,
+            guild_locale: value.guild_locale.as_deref().unwrap_or(&value.locale),
+        }
+    }
+}
+
+impl<'a> From<&'a ComponentInteraction> for Invoker<'a> {
+    fn from(value: &'a ComponentInteraction) -> Self {
+        Self {
+            guild_id: value.guild_id,
+            channel_id: value.channel_id,
+            user_id: value.user.id,
+            locale: &value.locale,
+            guild_locale: value.guild_locale.as_deref().unwrap_or(&value.locale),
+        }
+    }
+}
+
 /// Type used to monitor the responses sent by the bot.
 pub type AutoRemoverKey = (ChannelId, UserId);
 
+/// Type used to store a temporary state of each user, like the page they're viewing, removed after some time.
+pub type StateStore<T> = DashMap<AutoRemoverKey, (JoinHandle<()>, T)>;
+
+/// Type used to store the page each user is viewing in the paginated responses.
+pub type PageStore = StateStore<usize>;
+
+/// Handles a prefix command, like `!play`, returning `false` if the message isn't a supported command.
+///
+/// Only the music commands that don't need options besides the query are supported, using the guild's locale.
+pub async fn handle_prefix_command(
+    hydrogen: &HydrogenContext,
+    context: &Context,
+    message: &Message,
+    prefix: &str,
+) -> bool {
+    let Some(content) = message.content.strip_prefix(prefix) else {
+        return false;
+    };
+
+    // Prefix commands depend on the guild's voice state.
+    let Some(guild_id) = message.guild_id else {
+        return false;
+    };
+
+    let (name, args) = match content.split_once(char::is_whitespace) {
+        Some((name, args)) => (name, args.trim()),
+        None => (content, ""),
+    };
+
+    let locale = context
+        .cache
+        .guild(guild_id)
+        .map(|guild| guild.preferred_locale.clone())
+        .unwrap_or_default();
+
+    let invoker = Invoker {
+        guild_id: Some(guild_id),
+        channel_id: message.channel_id,
+        user_id: message.author.id,
+        locale: &locale,
+        guild_locale: &locale,
+    };
+
+    // Execute the command.
+    let response = match name {
+        "play" if !args.is_empty() => {
+            commands::play::play(hydrogen, context, &invoker, args, false).await
+        }
+        "skip" => components::skip::run(hydrogen, context, &invoker).await,
+        "prev" => components::prev::run(hydrogen, context, &invoker).await,
+        "pause" => components::pause::run(hydrogen, context, &invoker).await,
+        "stop" => components::stop::run(hydrogen, context, &invoker).await,
+        _ => return false,
+    };
+
+    // Get the footer's text.
+    let footer_text = hydrogen.i18n.translate(&locale, "generic", "embed_footer");
+
+    // Create the embed.
+    let message_builder = match response {
+        Ok(response) => create_message(response, hydrogen.primary_color, &footer_text),
+        Err(response) => create_message(response, hydrogen.error_color, &footer_text),
+    };
+
+    // Send the response to the channel.
+    if let Err(e) = message
+        .channel_id
+        .send_message(&context.http, message_builder)
+        .await
+    {
+        error!(
+            "(handle_prefix_command): cannot respond to the message: {}",
+            e
+        );
+    }
+
+    true
+}
+
 /// Handles a command interaction.
 pub async fn handle_command(
     hydrogen: &HydrogenContext,
     context: &Context,
     command: &CommandInteraction,
 ) {
-    if thread_rng().gen_bool(HYDROGEN_WARNING_PROBABILITY) && hydrogen.public_instance {
+    if let Some(sunset_time) = hydrogen
+        .sunset_notice
+        .filter(|_| thread_rng().gen_bool(HYDROGEN_WARNING_PROBABILITY))
+    {
         // Send a message to the user.
         if let Err(e) = command
-            .create_response(&context.http, hydrogen_end_message(command, &hydrogen.i18n))
+            .create_response(
+                &context.http,
+                hydrogen_end_message(command, &hydrogen.i18n, sunset_time),
+            )
             .await
         {
             error!("(handle_command): cannot respond to the interaction: {}", e);
@@ -80,6 +248,16 @@ pub async fn handle_command(
         "play" => commands::play::execute(hydrogen, context, command).await,
         "about" => commands::about::execute(hydrogen, context, command).await,
         "roll" => commands::roll::execute(hydrogen, context, command).await,
+        "volume" => commands::volume::execute(hydrogen, context, command).await,
+        "jump" => commands::jump::execute(hydrogen, context, command).await,
+        "announce" => commands::announce::execute(hydrogen, context, command).await,
+        "controls" => commands::controls::execute(hydrogen, context, command).await,
+        "nowplaying" => commands::nowplaying::execute(hydrogen, context, command).await,
+        "replay" => commands::replay::execute(hydrogen, context, command).await,
+        "settings" => commands::settings::execute(hydrogen, context, command).await,
+        "move" => commands::move_message::execute(hydrogen, context, command).await,
+        "shuffle" => commands::shuffle::execute(hydrogen, context, command).await,
+        "help" => commands::help::execute(hydrogen, context, command).await,
         _ => {
             error!("(handle_command): unknown command: {}", command.data.name);
             return;
@@ -93,13 +271,68 @@ pub async fn handle_command(
 
     // Create the embed.
     let message = match response {
-        Ok(response) => create_embed(response, HYDROGEN_PRIMARY_COLOR, &footer_text),
-        Err(response) => create_embed(response, HYDROGEN_ERROR_COLOR, &footer_text),
+        Ok(response) => create_embed(response, hydrogen.primary_color, &footer_text),
+        Err(response) => create_embed(response, hydrogen.error_color, &footer_text),
     };
 
     // Edit the response with the embed.
     if let Err(e) = command.edit_response(&context.http, message).await {
         error!("(handle_command): cannot respond to the interaction: {}", e);
+        return;
+    }
+
+    // Delete the response after some time, keeping the channel tidy.
+    if let Some(delay) = hydrogen.response_delete_delay {
+        let command = command.clone();
+        let http = context.http.clone();
+
+        spawn(async move {
+            delete_after(delay, &http, &command).await;
+        });
+    }
+}
+
+/// Deletes the command response after the delay.
+async fn delete_after(delay: Duration, http: &Http, command: &CommandInteraction) {
+    sleep(delay).await;
+    debug!(
+        "(delete_after): deleting the response of the command {}...",
+        command.id
+    );
+
+    if let Err(e) = command.delete_response(http).await {
+        warn!(
+            "(delete_after): cannot delete the response of the command {}: {}",
+            command.id, e
+        );
+    }
+}
+
+/// Type used to store the last time each user used a player control in a guild.
+pub type CooldownStore = DashMap<(GuildId, UserId), Instant>;
+
+/// Checks if the component controls the player, the only components limited by the cooldown.
+fn is_player_control(custom_id: &str) -> bool {
+    matches!(custom_id, "prev" | "pause" | "skip" | "loop" | "stop")
+}
+
+/// Starts the cooldown of the user, returning `false` if the previous one hasn't expired yet.
+fn start_cooldown(cooldowns: &CooldownStore, key: (GuildId, UserId), cooldown: Duration) -> bool {
+    match cooldowns.entry(key) {
+        Entry::Occupied(entry) if entry.get().elapsed() < cooldown => false,
+        entry => {
+            entry.insert(Instant::now());
+            true
+        }
+    }
+}
+
+/// Removes the expired cooldowns periodically, as each one is only checked again when its user clicks another control.
+pub async fn sweep_cooldowns(cooldowns: Arc<CooldownStore>, cooldown: Duration) {
+    loop {
+        sleep(Duration::from_secs(HYDROGEN_COOLDOWN_SWEEP_INTERVAL)).await;
+
+        cooldowns.retain(|_, last_use| last_use.elapsed() < cooldown);
     }
 }
 
@@ -115,11 +348,58 @@ pub async fn handle_component(
         return;
     }
 
+    // Check if the user is using the player controls too fast, the pages and confirmations aren't limited.
+    if let (Some(guild_id), true) = (
+        component.guild_id,
+        is_player_control(&component.data.custom_id),
+    ) {
+        if !start_cooldown(
+            &hydrogen.components_cooldown,
+            (guild_id, component.user.id),
+            hydrogen.component_cooldown,
+        ) {
+            debug!(
+                "(handle_component): user {} is in cooldown in the guild {}",
+                component.user.id, guild_id
+            );
+
+            let message = create_embed(
+                Response::Generic {
+                    title: hydrogen
+                        .i18n
+                        .translate(&component.locale, "cooldown", "embed_title"),
+                    description: hydrogen.i18n.translate(
+                        &component.locale,
+                        "cooldown",
+                        "slow_down",
+                    ),
+                },
+                hydrogen.error_color,
+                &hydrogen
+                    .i18n
+                    .translate(&component.locale, "generic", "embed_footer"),
+            );
+
+            if let Err(e) = component.edit_response(&context.http, message).await {
+                error!(
+                    "(handle_component): cannot respond to the interaction: {}",
+                    e
+                );
+            }
+
+            return;
+        }
+    }
+
     // Execute the component.
     let response = match component.data.custom_id.as_str() {
+        "duplicate_add" => components::duplicate::execute(hydrogen, context, component).await,
         "loop" => components::loop_switch::execute(hydrogen, context, component).await,
         "pause" => components::pause::execute(hydrogen, context, component).await,
         "prev" => components::prev::execute(hydrogen, context, component).await,
+        "queue" | "queue_prev" | "queue_next" => {
+            components::queue::execute(hydrogen, context, component).await
+        }
         "skip" => components::skip::execute(hydrogen, context, component).await,
         "stop" => components::stop::execute(hydrogen, context, component).await,
         _ => {
@@ -138,8 +418,8 @@ pub async fn handle_component(
 
     // Create the embed.
     let message = match response {
-        Ok(response) => create_embed(response, HYDROGEN_PRIMARY_COLOR, &footer_text),
-        Err(response) => create_embed(response, HYDROGEN_ERROR_COLOR, &footer_text),
+        Ok(response) => create_embed(response, hydrogen.primary_color, &footer_text),
+        Err(response) => create_embed(response, hydrogen.error_color, &footer_text),
     };
 
     // Edit the response with the embed.
@@ -185,17 +465,100 @@ pub async fn handle_component(
 /// Creates an Discord embed.
 fn create_embed(response: Response, color: i32, footer_text: &str) -> EditInteractionResponse {
     match response {
-        Response::Generic { title, description } => EditInteractionResponse::new().embed(
-            CreateEmbed::new()
-                .title(title)
-                .description(description)
-                .color(color)
-                .footer(CreateEmbedFooter::new(footer_text).icon_url(HYDROGEN_LOGO_URL)),
-        ),
+        Response::Generic { title, description } => EditInteractionResponse::new()
+            .embed(build_embed(title, description, color, footer_text)),
+        Response::Paginated {
+            title,
+            description,
+            id,
+            page,
+            pages,
+        } => EditInteractionResponse::new()
+            .embed(build_embed(title, description, color, footer_text))
+            .components(vec![page_buttons(id, page, pages)]),
+        Response::Confirm {
+            title,
+            description,
+            id,
+            label,
+        } => EditInteractionResponse::new()
+            .embed(build_embed(title, description, color, footer_text))
+            .components(vec![confirm_button(id, label)]),
+        Response::Track {
+            title,
+            description,
+            thumbnail,
+        } => EditInteractionResponse::new()
+            .embed(build_embed(title, description, color, footer_text).thumbnail(thumbnail)),
+    }
+}
+
+/// Creates a message with the Discord embed, used by the prefix commands.
+fn create_message(response: Response, color: i32, footer_text: &str) -> CreateMessage {
+    match response {
+        Response::Generic { title, description } => {
+            CreateMessage::new().embed(build_embed(title, description, color, footer_text))
+        }
+        Response::Paginated {
+            title,
+            description,
+            id,
+            page,
+            pages,
+        } => CreateMessage::new()
+            .embed(build_embed(title, description, color, footer_text))
+            .components(vec![page_buttons(id, page, pages)]),
+        Response::Confirm {
+            title,
+            description,
+            id,
+            label,
+        } => CreateMessage::new()
+            .embed(build_embed(title, description, color, footer_text))
+            .components(vec![confirm_button(id, label)]),
+        Response::Track {
+            title,
+            description,
+            thumbnail,
+        } => CreateMessage::new()
+            .embed(build_embed(title, description, color, footer_text).thumbnail(thumbnail)),
     }
 }
 
+/// Builds the embed used by the responses.
+fn build_embed(title: String, description: String, color: i32, footer_text: &str) -> CreateEmbed {
+    CreateEmbed::new()
+        .title(title)
+        .description(description)
+        .color(color)
+        .footer(CreateEmbedFooter::new(footer_text).icon_url(HYDROGEN_LOGO_URL))
+}
+
+/// Builds the button used to confirm the action of a confirmation response.
+fn confirm_button(id: &str, label: String) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![CreateButton::new(id)
+        .label(label)
+        .style(ButtonStyle::Primary)])
+}
+
+/// Builds the buttons used to change the page of a paginated response.
+fn page_buttons(id: &str, page: usize, pages: usize) -> CreateActionRow {
+    CreateActionRow::Buttons(vec![
+        CreateButton::new(format!("{}_prev", id))
+            .disabled(page == 0)
+            .emoji('◀')
+            .style(ButtonStyle::Secondary),
+        CreateButton::new(format!("{}_next", id))
+            .disabled(page + 1 >= pages)
+            .emoji('▶')
+            .style(ButtonStyle::Secondary),
+    ])
+}
+
 /// Registers the commands.
+///
+/// Music commands (`/join`, `/seek`, `/play`, `/volume`, `/jump`, `/announce`, `/controls`,
+/// `/nowplaying`, `/replay`, `/settings`, `/move` and `/shuffle`) are guild-only, while `/about`, `/roll` and `/help` can also be used in DMs.
 pub async fn register_commands(
     i18n: Option<&I18n>,
     http: impl AsRef<Http>,
@@ -204,21 +567,43 @@ pub async fn register_commands(
     // Prepare to write the commands' IDs.
     let mut commands_id = commands_id.write().await;
 
-    // Create an array of commands.
-    let commands = [
+    // Create the commands that depend on the guild's voice state, disallowing them in DMs.
+    let guild_commands = [
         commands::join::register(i18n),
         commands::seek::register(i18n),
         commands::play::register(i18n),
+        commands::volume::register(i18n),
+        commands::jump::register(i18n),
+        commands::announce::register(i18n),
+        commands::controls::register(i18n),
+        commands::nowplaying::register(i18n),
+        commands::replay::register(i18n),
+        commands::settings::register(i18n),
+        commands::move_message::register(i18n),
+        commands::shuffle::register(i18n),
+    ]
+    .map(|command| command.dm_permission(false));
+
+    // Create the commands that can be used anywhere, including DMs.
+    let global_commands = [
         commands::about::register(i18n),
         commands::roll::register(i18n),
-    ];
+        commands::help::register(i18n),
+    ]
+    .map(|command| command.dm_permission(true));
+
+    // Create a list with all commands.
+    let commands = guild_commands
+        .into_iter()
+        .chain(global_commands)
+        .collect::<Vec<_>>();
 
     // Register the commands.
     debug!(
         "(register_command): registering {} commands...",
         commands.len()
     );
-    match Command::set_global_commands(http, commands.to_vec()).await {
+    match Command::set_global_commands(http, commands).await {
         Ok(v) => {
             info!("(register_command): registered {} commands", v.len());
 
@@ -247,7 +632,58 @@ async fn autoremover(
     responses.remove(&key);
 }
 
-fn hydrogen_end_message(command: &CommandInteraction, i18n: &I18n) -> CreateInteractionResponse {
+/// Gets the page the user is viewing, starting at zero.
+pub fn get_page(pages: &PageStore, key: AutoRemoverKey) -> usize {
+    pages.get(&key).map(|v| v.1).unwrap_or_default()
+}
+
+/// Stores the page the user is viewing, removing it after some time without being used.
+pub fn set_page(pages: &Arc<PageStore>, key: AutoRemoverKey, page: usize) {
+    set_state(pages, key, page);
+}
+
+/// Stores the user's state, removing it after some time without being used.
+pub fn set_state<T: Send + Sync + 'static>(
+    store: &Arc<StateStore<T>>,
+    key: AutoRemoverKey,
+    state: T,
+) {
+    // Keep the store bounded, dropping any other state when it's full.
+    if store.len() >= HYDROGEN_PAGE_STATE_LIMIT && !store.contains_key(&key) {
+        let other_key = store.iter().next().map(|v| *v.key());
+
+        if let Some((_, (auto_remover, _))) = other_key.and_then(|v| store.remove(&v)) {
+            auto_remover.abort();
+        }
+    }
+
+    // Spawn the autoremover.
+    let store_clone = store.clone();
+    let auto_remover = spawn(async move {
+        sleep(Duration::from_secs(HYDROGEN_PAGE_STATE_TIMEOUT)).await;
+        debug!("(set_state): removing state {:?} from cache...", key);
+        store_clone.remove(&key);
+    });
+
+    // Store the state, aborting the old autoremover.
+    if let Some((old_auto_remover, _)) = store.insert(key, (auto_remover, state)) {
+        old_auto_remover.abort();
+    }
+}
+
+/// Takes the user's state out of the store, aborting its autoremover.
+pub fn take_state<T>(store: &StateStore<T>, key: AutoRemoverKey) -> Option<T> {
+    let (_, (auto_remover, state)) = store.remove(&key)?;
+    auto_remover.abort();
+
+    Some(state)
+}
+
+fn hydrogen_end_message(
+    command: &CommandInteraction,
+    i18n: &I18n,
+    sunset_time: u64,
+) -> CreateInteractionResponse {
     CreateInteractionResponse::Message(
         CreateInteractionResponseMessage::new()
             .ephemeral(true)
@@ -257,7 +693,7 @@ fn hydrogen_end_message(command: &CommandInteraction, i18n: &I18n) -> CreateInte
                     .description(format!(
                         "{}\n\n{}",
                         i18n.translate(&command.locale, "public_instance", "ending")
-                            .replace("{time}", "<t:1714489200>")
+                            .replace("{time}", &format!("<t:{}>", sunset_time))
                             .replace("{url}", HYDROGEN_REPOSITORY_URL),
                         i18n.translate(&command.locale, "public_instance", "running_in")
                             .replace(
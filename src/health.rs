@@ -0,0 +1,221 @@
+//! Hydrogen // Health
+//!
+//! Lightweight HTTP readiness endpoint, used by orchestrators like Kubernetes, also exposing the Lavalink nodes'
+//! connections in `/nodes`, the usage of the commands in `/commands` and, when a token is configured, a snapshot of the
+//! players in `/players`, used by dashboards.
+
+use std::{collections::HashMap, io, sync::Arc};
+
+use serenity::{
+    all::ShardId,
+    gateway::{ConnectionStage, ShardRunnerInfo},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    spawn,
+    sync::{Mutex, RwLock},
+};
+use tracing::{debug, error, info, warn};
+
+use crate::{manager::HydrogenManager, stats::UsageStats};
+
+/// Serves the readiness endpoint, responding `200` when the gateway and at least one Lavalink node are connected, or `503` otherwise.
+///
+/// The players' snapshot is only served with a `token`, as it contains the guilds, users and tracks of the players.
+pub async fn serve(
+    address: String,
+    token: Option<String>,
+    runners: Arc<Mutex<HashMap<ShardId, ShardRunnerInfo>>>,
+    manager: Arc<RwLock<Option<HydrogenManager>>>,
+    usage_stats: Arc<UsageStats>,
+) {
+    let listener = match TcpListener::bind(&address).await {
+        Ok(v) => v,
+        Err(e) => {
+            error!("(health): cannot listen on {}: {}", address, e);
+            return;
+        }
+    };
+
+    info!("(health): listening on {}", address);
+
+    loop {
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(e) => {
+                warn!("(health): cannot accept the connection: {}", e);
+                continue;
+            }
+        };
+
+        let token = token.clone();
+        let runners = runners.clone();
+        let manager = manager.clone();
+        let usage_stats = usage_stats.clone();
+        spawn(async move {
+            if let Err(e) =
+                respond(stream, token.as_deref(), &runners, &manager, &usage_stats).await
+            {
+                debug!("(health): cannot respond to the request: {}", e);
+            }
+        });
+    }
+}
+
+/// Checks if the gateway and at least one Lavalink node are connected.
+async fn is_ready(
+    runners: &Mutex<HashMap<ShardId, ShardRunnerInfo>>,
+    manager: &RwLock<Option<HydrogenManager>>,
+) -> bool {
+    let gateway_connected = {
+        let runners = runners.lock().await;
+        !runners.is_empty()
+            && runners
+                .values()
+                .all(|runner| runner.stage == ConnectionStage::Connected)
+    };
+
+    if !gateway_connected {
+        return false;
+    }
+
+    let Some(manager) = manager.read().await.clone() else {
+        return false;
+    };
+
+    manager.lavalink_node_count().await > 0
+}
+
+/// Gets the players' snapshot as JSON, or `None` if the manager isn't initialized yet.
+async fn players(manager: &RwLock<Option<HydrogenManager>>) -> Option<String> {
+    let manager = manager.read().await.clone()?;
+
+    match serde_json::to_string(&manager.snapshot().await) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            error!("(health): cannot serialize the players' snapshot: {}", e);
+            None
+        }
+    }
+}
+
+/// Gets the Lavalink nodes' snapshot as JSON, or `None` if the manager isn't initialized yet.
+async fn nodes(manager: &RwLock<Option<HydrogenManager>>) -> Option<String> {
+    let manager = manager.read().await.clone()?;
+
+    match serde_json::to_string(&manager.lavalink_snapshot().await) {
+        Ok(v) => Some(v),
+        Err(e) => {
+            error!("(health): cannot serialize the nodes' snapshot: {}", e);
+            None
+        }
+    }
+}
+
+/// Checks if the request has the `Authorization: Bearer <token>` header.
+fn is_authorized(request: &str, token: &str) -> bool {
+    request
+        .lines()
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .filter_map(|line| line.split_once(':'))
+        .any(|(name, value)| {
+            name.trim().eq_ignore_ascii_case("authorization")
+                && value.trim().strip_prefix("Bearer ") == Some(token)
+        })
+}
+
+/// Responds to the request with the players' snapshot in `/players`, the nodes' snapshot in `/nodes`, the commands'
+/// usage in `/commands`, or the readiness state in any other path, ignoring the method.
+async fn respond(
+    mut stream: TcpStream,
+    token: Option<&str>,
+    runners: &Mutex<HashMap<ShardId, ShardRunnerInfo>>,
+    manager: &RwLock<Option<HydrogenManager>>,
+    usage_stats: &UsageStats,
+) -> io::Result<()> {
+    let mut buffer = [0; 1024];
+    let length = stream.read(&mut buffer).await?;
+    debug!("(health): received a request with {} bytes", length);
+
+    // Get the path from the request line, like 'GET /players HTTP/1.1'.
+    let request = String::from_utf8_lossy(&buffer[..length]);
+    let path = request.split_whitespace().nth(1).unwrap_or("/");
+
+    let (status, content_type, body) = if path == "/players" {
+        match token {
+            Some(token) if is_authorized(&request, token) => match players(manager).await {
+                Some(body) => ("200 OK", "application/json", body),
+                None => (
+                    "503 Service Unavailable",
+                    "text/plain",
+                    "not ready".to_owned(),
+                ),
+            },
+            Some(_) => ("401 Unauthorized", "text/plain", "unauthorized".to_owned()),
+            None => ("404 Not Found", "text/plain", "not found".to_owned()),
+        }
+    } else if path == "/nodes" {
+        match nodes(manager).await {
+            Some(body) => ("200 OK", "application/json", body),
+            None => (
+                "503 Service Unavailable",
+                "text/plain",
+                "not ready".to_owned(),
+            ),
+        }
+    } else if path == "/commands" {
+        match serde_json::to_string(&usage_stats.snapshot()) {
+            Ok(body) => ("200 OK", "application/json", body),
+            Err(e) => {
+                error!("(health): cannot serialize the commands' usage: {}", e);
+                (
+                    "500 Internal Server Error",
+                    "text/plain",
+                    "error".to_owned(),
+                )
+            }
+        }
+    } else if is_ready(runners, manager).await {
+        ("200 OK", "text/plain", "ready".to_owned())
+    } else {
+        (
+            "503 Service Unavailable",
+            "text/plain",
+            "not ready".to_owned(),
+        )
+    };
+
+    stream
+        .write_all(
+            format!(
+                "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                content_type,
+                body.len(),
+                body
+            )
+            .as_bytes(),
+        )
+        .await?;
+
+    stream.shutdown().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::is_authorized;
+
+    #[test]
+    fn authorization_requires_the_token() {
+        let request =
+            "GET /players HTTP/1.1\r\nHost: localhost\r\nauthorization: Bearer secret\r\n\r\n";
+        assert!(is_authorized(request, "secret"));
+        assert!(!is_authorized(request, "other"));
+        assert!(!is_authorized(
+            "GET /players HTTP/1.1\r\nHost: localhost\r\n\r\n",
+            "secret"
+        ));
+    }
+}
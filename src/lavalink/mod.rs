@@ -1,4 +1,12 @@
-use std::{fmt::Display, result, sync::Arc, time::Duration};
+use std::{
+    fmt::{Debug, Display},
+    result,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
 use async_tungstenite::{
@@ -12,27 +20,41 @@ use async_tungstenite::{
     },
     WebSocketStream,
 };
-use futures::{stream::SplitStream, SinkExt, StreamExt};
+use dashmap::DashMap;
+use futures::{
+    stream::{SplitSink, SplitStream},
+    SinkExt, StreamExt,
+};
 use reqwest::{
-    header::{HeaderMap, InvalidHeaderValue},
-    Client,
+    header::{HeaderValue, InvalidHeaderValue, AUTHORIZATION},
+    Client, RequestBuilder,
 };
-use serde::Deserialize;
+#[cfg(feature = "health")]
+use serde::Serialize;
+use serde::{de::DeserializeOwned, Deserialize};
 use tokio::{
     net::TcpStream,
     select, spawn,
-    sync::{oneshot, RwLock},
+    sync::{oneshot, Mutex, Notify, RwLock},
     time::sleep,
 };
 use tokio_native_tls::TlsStream;
+use tracing::warn;
 
-use crate::LAVALINK_CONNECTION_TIMEOUT;
+use crate::{
+    LAVALINK_API_VERSION, LAVALINK_CONNECTION_TIMEOUT, LAVALINK_MAX_VOLUME,
+    LAVALINK_RECONNECT_DELAY, LAVALINK_RECONNECT_MAX_DELAY,
+};
 
 use self::{
-    rest::{LavalinkErrorResponse, LavalinkPlayer, LavalinkTrackLoading, LavalinkUpdatePlayer},
+    rest::{
+        LavalinkErrorResponse, LavalinkInfo, LavalinkPlayer, LavalinkTrackLoading,
+        LavalinkUpdatePlayer, LavalinkUpdateSession, LavalinkVoiceState,
+    },
     websocket::{
-        LavalinkReadyEvent, LavalinkTrackEndEvent, LavalinkTrackExceptionEvent,
-        LavalinkTrackStartEvent, LavalinkTrackStuckEvent,
+        LavalinkPlayerState, LavalinkPlayerUpdate, LavalinkReadyEvent, LavalinkStats,
+        LavalinkTrackEndEvent, LavalinkTrackEndReason, LavalinkTrackExceptionEvent,
+        LavalinkTrackStartEvent, LavalinkTrackStuckEvent, LavalinkWebSocketClosedEvent,
     },
 };
 
@@ -54,6 +76,13 @@ struct LavalinkInternalOp {
     pub op: LavalinkOpType,
 }
 
+/// Player returned by Lavalink, only with the guild ID.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LavalinkPlayerGuildId {
+    pub guild_id: String,
+}
+
 // This module will be removed once the Hydrolink project is complete.
 #[allow(clippy::enum_variant_names)]
 #[derive(Deserialize)]
@@ -86,9 +115,16 @@ pub trait LavalinkHandler {
     ) {
     }
     async fn lavalink_track_stuck(&self, _node: Lavalink, _message: LavalinkTrackStuckEvent) {}
+    async fn lavalink_websocket_closed(
+        &self,
+        _node: Lavalink,
+        _message: LavalinkWebSocketClosedEvent,
+    ) {
+    }
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum LavalinkError {
     Http(http::Error),
     WebSocket(tungstenite::Error),
@@ -115,7 +151,7 @@ impl Display for LavalinkError {
 
 pub type Result<T> = result::Result<T, LavalinkError>;
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum LavalinkConnection {
     Disconnected,
     Connecting,
@@ -127,91 +163,151 @@ pub struct LavalinkNodeInfo {
     pub host: String,
     pub password: String,
     pub tls: bool,
+    /// Interval in seconds between the keep-alive pings, `0` disables them.
+    pub ping_interval: u64,
+    /// Path where the Lavalink API is served, used behind reverse proxies.
+    pub path_prefix: Option<String>,
+    /// Times to try reconnecting after the connection drops, `0` disables it.
+    pub reconnect_attempts: u32,
+    /// Seconds the node keeps the session after the connection drops, resuming it when reconnecting, `0` disables it.
+    pub resume_timeout: u32,
+}
+
+impl Debug for LavalinkNodeInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LavalinkNodeInfo")
+            .field("host", &self.host)
+            .field("password", &REDACTED)
+            .field("tls", &self.tls)
+            .field("ping_interval", &self.ping_interval)
+            .field("path_prefix", &self.path_prefix)
+            .field("reconnect_attempts", &self.reconnect_attempts)
+            .field("resume_timeout", &self.resume_timeout)
+            .finish()
+    }
 }
 
+/// Shown instead of the secrets, like passwords and tokens, in the `Debug` output.
+pub const REDACTED: &str = "[redacted]";
+
+/// ID given to the next Lavalink connection.
+static NEXT_LAVALINK_ID: AtomicUsize = AtomicUsize::new(0);
+
+/// Connection to a Lavalink node.
+///
+/// Cloning is cheap and the clones share the same connection and session, comparing equal to each other.
 #[derive(Clone)]
 pub struct Lavalink {
+    /// Unique ID of this connection, shared by its clones.
+    id: usize,
+    /// HTTP client shared by every node, reusing the connection pool.
     http_client: Client,
+    /// Password sent in the `Authorization` header of the REST requests.
+    authorization: HeaderValue,
     tls: bool,
     host: Arc<String>,
+    path_prefix: Arc<String>,
     session_id: Arc<RwLock<String>>,
     connected: Arc<RwLock<LavalinkConnection>>,
-    // connection: Arc<Mutex<SplitSink<WebSocketStream<TokioAdapter<TcpStream>, TokioAdapter<TlsStream<TcpStream>>>, Message>>>
+    /// Write half of the WebSocket, replaced when reconnecting.
+    connection: Arc<Mutex<LavalinkSink>>,
+    /// If the connection was closed on purpose, avoiding reconnecting.
+    closed: Arc<AtomicBool>,
+    /// When the current session was established, `None` while disconnected.
+    connected_at: Arc<RwLock<Option<Instant>>>,
+    /// How many times the connection was reopened after dropping.
+    reconnects: Arc<AtomicUsize>,
+    /// Key sent when reconnecting to resume the session, `None` if the resuming is disabled.
+    resume_key: Arc<RwLock<Option<String>>>,
+    /// Seconds the node keeps the session after the connection drops, `0` if the resuming is disabled.
+    resume_timeout: u32,
+    /// Last state sent by the node for each player, removed when the track ends.
+    player_states: Arc<DashMap<u64, LavalinkPlayerState>>,
+    /// Last resource usage sent by the node, `None` until the first stats message.
+    stats: Arc<RwLock<Option<LavalinkStats>>>,
+}
+
+/// Read-only snapshot of a node's connection, used by external tooling like dashboards.
+#[cfg(feature = "health")]
+#[derive(Debug, Serialize)]
+pub struct LavalinkNodeSnapshot {
+    pub host: String,
+    pub connected: bool,
+    /// Seconds since the current session was established, `None` while disconnected.
+    pub uptime: Option<u64>,
+    pub reconnects: usize,
+}
+
+impl Debug for Lavalink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Lavalink")
+            .field("id", &self.id)
+            .field("host", &self.host)
+            .field("tls", &self.tls)
+            .field("path_prefix", &self.path_prefix)
+            .field("closed", &self.closed.load(Ordering::Relaxed))
+            .field("reconnects", &self.reconnect_count())
+            .finish_non_exhaustive()
+    }
 }
 
 impl Lavalink {
+    /// Connects to the node, sending the REST requests through `http_client`, which can be shared by many nodes.
     pub async fn connect<H: LavalinkHandler + Sync + Send + 'static>(
         node: LavalinkNodeInfo,
         user_id: u64,
+        http_client: Client,
         handler: H,
     ) -> Result<Self> {
-        let websocket_uri = format!(
-            "{}://{}/v3/websocket",
-            match node.tls {
-                true => "wss",
-                false => "ws",
-            },
-            node.host
-        );
+        let path_prefix = normalize_path_prefix(node.path_prefix.as_deref());
 
-        let http_client = Client::builder()
-            .default_headers({
-                let mut headers = HeaderMap::new();
-                headers.insert(
-                    "Authorization",
-                    node.password
-                        .parse()
-                        .map_err(LavalinkError::InvalidHeaderValue)?,
-                );
-                headers
-            })
-            .user_agent("hydrogen/0.0.1")
-            .build()
-            .map_err(LavalinkError::Reqwest)?;
-
-        let request = Request::builder()
-            .header("Host", websocket_uri.clone())
-            .header("Connection", "Upgrade")
-            .header("Upgrade", "websocket")
-            .header("Sec-WebSocket-Version", "13")
-            .header("Sec-WebSocket-Key", generate_key())
-            .header("Authorization", node.password.clone())
-            .header("User-Id", user_id)
-            .header("Client-Name", "hydrogen/0.0.1")
-            .uri(websocket_uri)
-            .body(())
-            .map_err(LavalinkError::Http)?;
-
-        let (mut sink, stream) = connect_async(request)
-            .await
-            .map_err(LavalinkError::WebSocket)?
-            .0
-            .split();
+        let mut authorization =
+            HeaderValue::from_str(&node.password).map_err(LavalinkError::InvalidHeaderValue)?;
+        authorization.set_sensitive(true);
+
+        let (sink, stream) = open_socket(&node, user_id, None).await?;
 
         let lavalink = Self {
+            id: NEXT_LAVALINK_ID.fetch_add(1, Ordering::Relaxed),
             session_id: Arc::new(RwLock::new(String::new())),
-            host: Arc::new(node.host),
+            host: Arc::new(node.host.clone()),
+            path_prefix: Arc::new(path_prefix),
             connected: Arc::new(RwLock::new(LavalinkConnection::Connecting)),
             tls: node.tls,
-            // connection: Arc::new(Mutex::new(sink)),
+            connection: Arc::new(Mutex::new(sink)),
+            closed: Arc::new(AtomicBool::new(false)),
+            connected_at: Arc::new(RwLock::new(None)),
+            reconnects: Arc::new(AtomicUsize::new(0)),
+            resume_key: Arc::new(RwLock::new(None)),
+            // Resuming is useless without reconnecting.
+            resume_timeout: match node.reconnect_attempts {
+                0 => 0,
+                _ => node.resume_timeout,
+            },
+            player_states: Arc::new(DashMap::new()),
+            stats: Arc::new(RwLock::new(None)),
             http_client,
+            authorization,
         };
 
         let (sender, mut receiver) = oneshot::channel();
 
         let lavalink_clone = lavalink.clone();
-        spawn(async move {
-            read_socket(handler, lavalink_clone, Some(sender), stream).await;
+        let read_handle = spawn(async move {
+            run_socket(handler, lavalink_clone, node, user_id, sender, stream).await;
         });
 
+        // The reader task is aborted when the connection fails, avoiding it to call the disconnect handler for a node that never connected.
         select! {
             _ = sleep(Duration::from_millis(LAVALINK_CONNECTION_TIMEOUT)) => {
-                _ = sink.close().await;
+                read_handle.abort();
+                _ = lavalink.close().await;
                 Err(LavalinkError::NotConnected)
             }
             msg = &mut receiver => {
                 if msg.is_err() {
-                    _ = sink.close().await;
+                    read_handle.abort();
+                    _ = lavalink.close().await;
                     return Err(LavalinkError::NotConnected);
                 }
 
@@ -224,121 +320,471 @@ impl Lavalink {
         self.connected.read().await.clone()
     }
 
+    /// Gets how long the current session has been established, `None` while disconnected.
+    pub async fn uptime(&self) -> Option<Duration> {
+        self.connected_at.read().await.map(|v| v.elapsed())
+    }
+
+    /// Gets the address of the node.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Gets how many times the connection was reopened after dropping.
+    pub fn reconnect_count(&self) -> usize {
+        self.reconnects.load(Ordering::Relaxed)
+    }
+
+    /// Gets the last resource usage sent by the node, `None` if it wasn't sent yet.
+    pub async fn stats(&self) -> Option<LavalinkStats> {
+        self.stats.read().await.clone()
+    }
+
+    /// Gets a snapshot of the connection.
+    #[cfg(feature = "health")]
+    pub async fn snapshot(&self) -> LavalinkNodeSnapshot {
+        LavalinkNodeSnapshot {
+            host: self.host.to_string(),
+            connected: self.connected().await == LavalinkConnection::Connected,
+            uptime: self.uptime().await.map(|v| v.as_secs()),
+            reconnects: self.reconnect_count(),
+        }
+    }
+
+    /// Sends a frame through the WebSocket.
+    pub async fn send_message(&self, message: Message) -> Result<()> {
+        self.connection
+            .lock()
+            .await
+            .send(message)
+            .await
+            .map_err(LavalinkError::WebSocket)
+    }
+
+    /// Closes the WebSocket without reconnecting and without calling the disconnect handler.
+    pub async fn close(&self) -> Result<()> {
+        self.closed.store(true, Ordering::Release);
+
+        self.connection
+            .lock()
+            .await
+            .close()
+            .await
+            .map_err(LavalinkError::WebSocket)
+    }
+
+    /// Updates the player, sending every field set in `player` in a single request.
+    ///
+    /// If `no_replace` is `true`, the track isn't replaced when the player is already playing one.
     pub async fn update_player(
         &self,
         guild_id: u64,
         no_replace: bool,
         player: &LavalinkUpdatePlayer,
     ) -> Result<LavalinkPlayer> {
-        let response = self
-            .http_client
-            .patch(format!(
-                "{}://{}/v3/sessions/{}/players/{}?noReplace={}",
-                match self.tls {
-                    true => "https",
-                    false => "http",
-                },
-                self.host,
+        send(self.authorize(update_player_request(
+            &self.http_client,
+            self.build_rest_uri(&format!(
+                "sessions/{}/players/{}",
                 self.session_id.read().await.clone(),
-                guild_id,
-                no_replace
-            ))
-            .json(&player)
-            .send()
-            .await
-            .map_err(LavalinkError::Reqwest)?
-            .bytes()
-            .await
-            .map_err(LavalinkError::Reqwest)?;
+                guild_id
+            )),
+            no_replace,
+            player,
+        )))
+        .await
+    }
 
-        parse_response(&response)
+    /// Updates only the player's voice state, without touching the track, like after the gateway reconnected.
+    pub async fn update_voice(
+        &self,
+        guild_id: u64,
+        voice: LavalinkVoiceState,
+    ) -> Result<LavalinkPlayer> {
+        let mut player = LavalinkUpdatePlayer::new();
+        player.voice_state(voice);
+
+        self.update_player(guild_id, true, &player).await
     }
 
     pub async fn track_load(&self, identifier: &str) -> Result<LavalinkTrackLoading> {
-        let response = self
-            .http_client
-            .get(format!(
-                "{}://{}/v3/loadtracks?identifier={}",
-                match self.tls {
-                    true => "https",
-                    false => "http",
-                },
-                self.host,
-                identifier
-            ))
-            .send()
+        send(self.authorize(track_load_request(
+            &self.http_client,
+            self.build_rest_uri("loadtracks"),
+            identifier,
+        )))
+        .await
+    }
+
+    pub async fn get_player(&self, guild_id: u64) -> Result<LavalinkPlayer> {
+        self.get(&format!(
+            "sessions/{}/players/{}",
+            self.session_id.read().await.clone(),
+            guild_id
+        ))
+        .await
+    }
+
+    /// Changes the volume of the player without replacing its track, clamped to the range accepted by Lavalink.
+    pub async fn set_volume(&self, guild_id: u64, volume: u16) -> Result<LavalinkPlayer> {
+        self.update_player(guild_id, true, &volume_update(volume))
             .await
-            .map_err(LavalinkError::Reqwest)?
-            .bytes()
+    }
+
+    /// Enables or disables the resuming of the session, keeping it for `timeout` seconds after the connection drops.
+    ///
+    /// When enabled, the reconnections send the resuming key, so the node re-attaches the players of the session.
+    pub async fn update_session(
+        &self,
+        resuming: bool,
+        timeout: u32,
+    ) -> Result<LavalinkUpdateSession> {
+        let session_id = self.session_id.read().await.clone();
+        let session = LavalinkUpdateSession {
+            // The session ID is unique, so it's also used as the resuming key.
+            resuming_key: resuming.then(|| session_id.clone()),
+            timeout: Some(timeout),
+        };
+
+        let session: LavalinkUpdateSession = send(
+            self.authorize(
+                self.http_client
+                    .patch(self.build_rest_uri(&format!("sessions/{}", session_id)))
+                    .json(&session),
+            ),
+        )
+        .await?;
+
+        self.resume_key
+            .write()
             .await
-            .map_err(LavalinkError::Reqwest)?;
+            .clone_from(&session.resuming_key);
 
-        parse_response(&response)
+        Ok(session)
     }
 
-    pub async fn get_player(&self, guild_id: u64) -> Result<LavalinkPlayer> {
-        let response = self
-            .http_client
-            .get(format!(
-                "{}://{}/v3/sessions/{}/players/{}",
-                match self.tls {
-                    true => "https",
-                    false => "http",
-                },
-                self.host,
-                self.session_id.read().await.clone(),
-                guild_id
+    /// Gets the last state sent by the node for the player, without requesting it.
+    ///
+    /// The state is sent every few seconds, so the position needs to be advanced by the time since it was sent.
+    pub fn last_player_update(&self, guild_id: u64) -> Option<LavalinkPlayerState> {
+        self.player_states.get(&guild_id).map(|v| *v)
+    }
+
+    /// Lists the IDs of the guilds with a player in this session.
+    pub async fn player_guild_ids(&self) -> Result<Vec<u64>> {
+        let players: Vec<LavalinkPlayerGuildId> = self
+            .get(&format!(
+                "sessions/{}/players",
+                self.session_id.read().await.clone()
             ))
-            .send()
-            .await
-            .map_err(LavalinkError::Reqwest)?
-            .bytes()
-            .await
-            .map_err(LavalinkError::Reqwest)?;
+            .await?;
 
-        parse_response(&response)
+        Ok(players
+            .into_iter()
+            .filter_map(|player| player.guild_id.parse().ok())
+            .collect())
     }
 
     pub async fn destroy_player(&self, guild_id: u64) -> Result<()> {
-        self.http_client
-            .delete(format!(
-                "{}://{}/v3/sessions/{}/players/{}",
-                match self.tls {
-                    true => "https",
-                    false => "http",
-                },
-                self.host,
-                self.session_id.read().await.clone(),
-                guild_id
-            ))
-            .send()
-            .await
-            .map_err(LavalinkError::Reqwest)?
-            .bytes()
-            .await
-            .map_err(LavalinkError::Reqwest)?;
+        self.player_states.remove(&guild_id);
+
+        self.authorize(self.http_client.delete(self.build_rest_uri(&format!(
+            "sessions/{}/players/{}",
+            self.session_id.read().await.clone(),
+            guild_id
+        ))))
+        .send()
+        .await
+        .map_err(LavalinkError::Reqwest)?
+        .bytes()
+        .await
+        .map_err(LavalinkError::Reqwest)?;
 
         Ok(())
     }
 
-    pub async fn eq(&self, other: &Self) -> bool {
-        self.session_id.read().await.clone() == other.session_id.read().await.clone()
-            && self.host == other.host
-            && self.connected.read().await.clone() == other.connected.read().await.clone()
+    /// Gets the information about the node, like its version and the enabled sources.
+    ///
+    /// Only the password is needed, so it doesn't depend on the session, working before the node is ready.
+    pub async fn info(&self) -> Result<LavalinkInfo> {
+        self.get("info").await
+    }
+
+    /// Sends a GET request to a REST endpoint, relative to the API version.
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T> {
+        send(self.authorize(self.http_client.get(self.build_rest_uri(path)))).await
+    }
+
+    /// Adds the node's password to the request, as the HTTP client is shared with other nodes.
+    fn authorize(&self, request: RequestBuilder) -> RequestBuilder {
+        request.header(AUTHORIZATION, self.authorization.clone())
+    }
+
+    /// Builds the URI of a REST endpoint, relative to the API version.
+    fn build_rest_uri(&self, path: &str) -> String {
+        build_rest_uri(self.tls, &self.host, &self.path_prefix, path)
     }
 }
 
-type LavalinkStream = SplitStream<
-    WebSocketStream<Stream<TokioAdapter<TcpStream>, TokioAdapter<TlsStream<TcpStream>>>>,
->;
+impl PartialEq for Lavalink {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
 
-async fn read_socket<H: LavalinkHandler + Sync + Send + 'static>(
+impl Eq for Lavalink {}
+
+/// Builds the HTTP client shared by the nodes, the password is added to each request.
+pub fn http_client() -> Client {
+    Client::builder()
+        .user_agent("hydrogen/0.0.1")
+        .build()
+        .unwrap_or_default()
+}
+
+/// Normalizes the path prefix, keeping only a leading slash or returning an empty string if there's no prefix.
+fn normalize_path_prefix(path_prefix: Option<&str>) -> String {
+    match path_prefix.map(|v| v.trim_matches('/')) {
+        Some(v) if !v.is_empty() => format!("/{}", v),
+        _ => String::new(),
+    }
+}
+
+/// Builds the URI of a REST endpoint, the path prefix needs to be normalized.
+fn build_rest_uri(tls: bool, host: &str, path_prefix: &str, path: &str) -> String {
+    format!(
+        "{}://{}{}/v{}/{}",
+        match tls {
+            true => "https",
+            false => "http",
+        },
+        host,
+        path_prefix,
+        LAVALINK_API_VERSION,
+        path
+    )
+}
+
+/// Builds the request to load the tracks, encoding the identifier in the query string.
+fn track_load_request(client: &Client, uri: String, identifier: &str) -> RequestBuilder {
+    client.get(uri).query(&[("identifier", identifier)])
+}
+
+/// Builds the request to update the player, telling Lavalink if the current track can be replaced.
+fn update_player_request(
+    client: &Client,
+    uri: String,
+    no_replace: bool,
+    player: &LavalinkUpdatePlayer,
+) -> RequestBuilder {
+    client
+        .patch(uri)
+        .query(&[("noReplace", no_replace)])
+        .json(player)
+}
+
+/// Sends the request, parsing the response.
+async fn send<T: DeserializeOwned>(request: RequestBuilder) -> Result<T> {
+    let response = request
+        .send()
+        .await
+        .map_err(LavalinkError::Reqwest)?
+        .bytes()
+        .await
+        .map_err(LavalinkError::Reqwest)?;
+
+    parse_response(&response)
+}
+
+/// Builds the URI of the WebSocket, the path prefix needs to be normalized.
+fn build_websocket_uri(tls: bool, host: &str, path_prefix: &str) -> String {
+    format!(
+        "{}://{}{}/v{}/websocket",
+        match tls {
+            true => "wss",
+            false => "ws",
+        },
+        host,
+        path_prefix,
+        LAVALINK_API_VERSION
+    )
+}
+
+type LavalinkWebSocket =
+    WebSocketStream<Stream<TokioAdapter<TcpStream>, TokioAdapter<TlsStream<TcpStream>>>>;
+
+type LavalinkStream = SplitStream<LavalinkWebSocket>;
+
+type LavalinkSink = SplitSink<LavalinkWebSocket, Message>;
+
+/// State shared between the socket reader and the keep-alive pings.
+#[derive(Default)]
+struct LavalinkKeepAlive {
+    /// If any message was received since the last ping.
+    alive: AtomicBool,
+    /// Notified when the connection is considered dead.
+    dead: Notify,
+}
+
+/// Opens the WebSocket connection to the node, splitting it into its write and read halves.
+///
+/// The `resume_key` is sent to resume the session instead of creating a new one.
+async fn open_socket(
+    node: &LavalinkNodeInfo,
+    user_id: u64,
+    resume_key: Option<&str>,
+) -> Result<(LavalinkSink, LavalinkStream)> {
+    let path_prefix = normalize_path_prefix(node.path_prefix.as_deref());
+    let websocket_uri = build_websocket_uri(node.tls, &node.host, &path_prefix);
+
+    let mut request = Request::builder();
+    if let Some(resume_key) = resume_key {
+        request = request.header("Resume-Key", resume_key);
+    }
+
+    let request = request
+        .header("Host", websocket_uri.clone())
+        .header("Connection", "Upgrade")
+        .header("Upgrade", "websocket")
+        .header("Sec-WebSocket-Version", "13")
+        .header("Sec-WebSocket-Key", generate_key())
+        .header("Authorization", node.password.clone())
+        .header("User-Id", user_id)
+        .header("Client-Name", "hydrogen/0.0.1")
+        .uri(websocket_uri)
+        .body(())
+        .map_err(LavalinkError::Http)?;
+
+    Ok(connect_async(request)
+        .await
+        .map_err(LavalinkError::WebSocket)?
+        .0
+        .split())
+}
+
+/// Calculates the delay before a reconnection attempt, doubling it after each failed attempt.
+fn reconnect_delay(attempt: u32) -> Duration {
+    Duration::from_millis(
+        LAVALINK_RECONNECT_DELAY
+            .saturating_mul(2u64.saturating_pow(attempt))
+            .min(LAVALINK_RECONNECT_MAX_DELAY),
+    )
+}
+
+/// Reads the socket, reconnecting when the connection drops until the attempts run out.
+async fn run_socket<H: LavalinkHandler + Sync + Send + 'static>(
     handler: H,
     origin: Lavalink,
-    mut sender: Option<oneshot::Sender<()>>,
+    node: LavalinkNodeInfo,
+    user_id: u64,
+    sender: oneshot::Sender<()>,
+    mut stream: LavalinkStream,
+) {
+    let mut sender = Some(sender);
+
+    loop {
+        let keep_alive = Arc::new(LavalinkKeepAlive::default());
+
+        let ping_handle = (node.ping_interval > 0).then(|| {
+            let origin = origin.clone();
+            let interval = Duration::from_secs(node.ping_interval);
+            let keep_alive = keep_alive.clone();
+
+            spawn(async move {
+                ping_socket(origin, interval, keep_alive).await;
+            })
+        });
+
+        read_socket(&handler, &origin, &mut sender, stream, keep_alive).await;
+
+        if let Some(ping_handle) = ping_handle {
+            ping_handle.abort();
+        }
+        *origin.connected_at.write().await = None;
+
+        // The node never connected, `connect` reports it to the caller, or was closed on purpose.
+        if sender.is_some() || origin.closed.load(Ordering::Acquire) {
+            *origin.connected.write().await = LavalinkConnection::Disconnected;
+            return;
+        }
+
+        // Tell the handler the node is reconnecting, so it doesn't give up while waiting.
+        if node.reconnect_attempts > 0 {
+            *origin.connected.write().await = LavalinkConnection::Connecting;
+            handler.lavalink_disconnect(origin.clone()).await;
+        }
+
+        match reconnect(&origin, &node, user_id).await {
+            Some(new_stream) => stream = new_stream,
+            None => {
+                *origin.connected.write().await = LavalinkConnection::Disconnected;
+                handler.lavalink_disconnect(origin).await;
+                return;
+            }
+        }
+    }
+}
+
+/// Tries to open the socket again, replacing the write half, returning `None` if all the attempts failed.
+async fn reconnect(
+    origin: &Lavalink,
+    node: &LavalinkNodeInfo,
+    user_id: u64,
+) -> Option<LavalinkStream> {
+    for attempt in 0..node.reconnect_attempts {
+        sleep(reconnect_delay(attempt)).await;
+
+        let resume_key = origin.resume_key.read().await.clone();
+
+        match open_socket(node, user_id, resume_key.as_deref()).await {
+            Ok((sink, stream)) => {
+                *origin.connection.lock().await = sink;
+                origin.reconnects.fetch_add(1, Ordering::Relaxed);
+                return Some(stream);
+            }
+            Err(e) => warn!(
+                "cannot reconnect to the lavalink node {} (attempt {}/{}): {}",
+                node.host,
+                attempt + 1,
+                node.reconnect_attempts,
+                e
+            ),
+        }
+    }
+
+    None
+}
+
+/// Pings the Lavalink periodically, considering the connection dead if nothing is received between two pings.
+async fn ping_socket(origin: Lavalink, interval: Duration, keep_alive: Arc<LavalinkKeepAlive>) {
+    loop {
+        sleep(interval).await;
+
+        if !keep_alive.alive.swap(false, Ordering::AcqRel)
+            || origin
+                .send_message(Message::Ping(Vec::new()))
+                .await
+                .is_err()
+        {
+            keep_alive.dead.notify_one();
+            break;
+        }
+    }
+}
+
+async fn read_socket<H: LavalinkHandler + Sync + Send + 'static>(
+    handler: &H,
+    origin: &Lavalink,
+    sender: &mut Option<oneshot::Sender<()>>,
     mut stream: LavalinkStream,
+    keep_alive: Arc<LavalinkKeepAlive>,
 ) {
-    while let Some(Ok(message)) = stream.next().await {
+    while let Some(Ok(message)) = select! {
+        message = stream.next() => message,
+        _ = keep_alive.dead.notified() => None,
+    } {
+        keep_alive.alive.store(true, Ordering::Release);
+
         if let Message::Text(message_str) = message {
             if let Ok(op) = serde_json::from_str::<LavalinkInternalOp>(&message_str) {
                 match op.op {
@@ -351,18 +797,43 @@ async fn read_socket<H: LavalinkHandler + Sync + Send + 'static>(
                                 .await
                                 .replace_range(.., &ready.session_id);
                             *origin.connected.write().await = LavalinkConnection::Connected;
+                            *origin.connected_at.write().await = Some(Instant::now());
 
-                            if let Some(some_sender) = sender {
+                            if let Some(some_sender) = sender.take() {
                                 if some_sender.send(()).is_err() {
                                     break;
                                 }
+                            }
 
-                                sender = None;
+                            // Keep the session after the connection drops, allowing the reconnection to resume it.
+                            if origin.resume_timeout > 0 {
+                                if let Err(e) =
+                                    origin.update_session(true, origin.resume_timeout).await
+                                {
+                                    warn!(
+                                        "cannot enable the resuming in the lavalink node {}: {}",
+                                        origin.host, e
+                                    );
+                                }
                             }
 
                             handler.lavalink_ready(origin.clone(), ready.resumed).await;
                         }
                     }
+                    LavalinkOpType::PlayerUpdate => {
+                        if let Ok(update) =
+                            serde_json::from_str::<LavalinkPlayerUpdate>(&message_str)
+                        {
+                            if let Ok(guild_id) = update.guild_id.parse() {
+                                origin.player_states.insert(guild_id, update.state);
+                            }
+                        }
+                    }
+                    LavalinkOpType::Stats => {
+                        if let Ok(stats) = serde_json::from_str::<LavalinkStats>(&message_str) {
+                            *origin.stats.write().await = Some(stats);
+                        }
+                    }
                     LavalinkOpType::Event => {
                         if let Ok(event) =
                             serde_json::from_str::<LavalinkInternalEvent>(&message_str)
@@ -383,6 +854,14 @@ async fn read_socket<H: LavalinkHandler + Sync + Send + 'static>(
                                     if let Ok(track_end) =
                                         serde_json::from_str::<LavalinkTrackEndEvent>(&message_str)
                                     {
+                                        // A replaced track is followed by the next one, keeping the state.
+                                        if track_end.reason != LavalinkTrackEndReason::Replaced {
+                                            if let Ok(guild_id) = track_end.guild_id.parse::<u64>()
+                                            {
+                                                origin.player_states.remove(&guild_id);
+                                            }
+                                        }
+
                                         handler.lavalink_track_end(origin.clone(), track_end).await;
                                     }
                                 }
@@ -411,17 +890,34 @@ async fn read_socket<H: LavalinkHandler + Sync + Send + 'static>(
                                             .await;
                                     }
                                 }
-                                _ => (),
+                                LavalinkEventType::WebSocketClosedEvent => {
+                                    if let Ok(websocket_closed) =
+                                        serde_json::from_str::<LavalinkWebSocketClosedEvent>(
+                                            &message_str,
+                                        )
+                                    {
+                                        handler
+                                            .lavalink_websocket_closed(
+                                                origin.clone(),
+                                                websocket_closed,
+                                            )
+                                            .await;
+                                    }
+                                }
                             }
                         }
                     }
-                    _ => (),
                 }
             }
         }
     }
-    *origin.connected.write().await = LavalinkConnection::Disconnected;
-    handler.lavalink_disconnect(origin).await;
+}
+
+/// Creates the update changing only the volume, clamped to the range accepted by Lavalink.
+fn volume_update(volume: u16) -> LavalinkUpdatePlayer {
+    let mut player = LavalinkUpdatePlayer::new();
+    player.volume(volume.min(LAVALINK_MAX_VOLUME));
+    player
 }
 
 fn parse_response<'a, T: Deserialize<'a>>(response: &'a [u8]) -> Result<T> {
@@ -432,3 +928,303 @@ fn parse_response<'a, T: Deserialize<'a>>(response: &'a [u8]) -> Result<T> {
         }
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use futures::future::join_all;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::lavalink::rest::{LavalinkException, LavalinkSeverity};
+
+    /// Handler holding a reference to the counter, dropped with the reader task that owns it.
+    struct CountedHandler {
+        _counter: Arc<()>,
+    }
+
+    #[async_trait]
+    impl LavalinkHandler for CountedHandler {}
+
+    #[tokio::test]
+    async fn failed_connects_dont_leak_reader_tasks() {
+        // Accept the WebSockets without ever sending the ready message, making every connection time out.
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                if let Ok(socket) = async_tungstenite::tokio::accept_async(stream).await {
+                    sockets.push(socket);
+                }
+            }
+        });
+
+        let node = LavalinkNodeInfo {
+            host: address.to_string(),
+            password: "youshallnotpass".to_owned(),
+            tls: false,
+            ping_interval: 0,
+            path_prefix: None,
+            reconnect_attempts: 0,
+            resume_timeout: 0,
+        };
+        let counter = Arc::new(());
+
+        let results = join_all((0..16).map(|_| {
+            Lavalink::connect(
+                node.clone(),
+                1,
+                Client::new(),
+                CountedHandler {
+                    _counter: counter.clone(),
+                },
+            )
+        }))
+        .await;
+        assert!(results
+            .iter()
+            .all(|v| matches!(v, Err(LavalinkError::NotConnected))));
+
+        // The aborted tasks are dropped the next time the runtime polls them.
+        sleep(Duration::from_millis(100)).await;
+        assert_eq!(Arc::strong_count(&counter), 1);
+    }
+
+    #[test]
+    fn node_info_debug_hides_the_password() {
+        let node = LavalinkNodeInfo {
+            host: "127.0.0.1:2333".to_owned(),
+            password: "youshallnotpass".to_owned(),
+            tls: false,
+            ping_interval: 30,
+            path_prefix: None,
+            reconnect_attempts: 5,
+            resume_timeout: 60,
+        };
+
+        let output = format!("{:?}", node);
+        assert!(output.contains("127.0.0.1:2333"));
+        assert!(!output.contains("youshallnotpass"));
+    }
+
+    #[test]
+    fn reconnect_delay_doubles_until_max() {
+        assert_eq!(
+            reconnect_delay(0),
+            Duration::from_millis(LAVALINK_RECONNECT_DELAY)
+        );
+        assert_eq!(
+            reconnect_delay(1),
+            Duration::from_millis(LAVALINK_RECONNECT_DELAY * 2)
+        );
+        assert_eq!(
+            reconnect_delay(u32::MAX),
+            Duration::from_millis(LAVALINK_RECONNECT_MAX_DELAY)
+        );
+    }
+
+    #[test]
+    fn path_prefix_without_slashes() {
+        let prefix = normalize_path_prefix(Some("lavalink"));
+
+        assert_eq!(
+            build_rest_uri(false, "localhost:2333", &prefix, "loadtracks"),
+            "http://localhost:2333/lavalink/v3/loadtracks"
+        );
+        assert_eq!(
+            build_websocket_uri(true, "localhost:2333", &prefix),
+            "wss://localhost:2333/lavalink/v3/websocket"
+        );
+    }
+
+    #[test]
+    fn path_prefix_with_slashes() {
+        let prefix = normalize_path_prefix(Some("/proxy/lavalink/"));
+
+        assert_eq!(
+            build_rest_uri(true, "example.com", &prefix, "loadtracks"),
+            "https://example.com/proxy/lavalink/v3/loadtracks"
+        );
+        assert_eq!(
+            build_websocket_uri(false, "example.com", &prefix),
+            "ws://example.com/proxy/lavalink/v3/websocket"
+        );
+    }
+
+    #[test]
+    fn empty_path_prefix() {
+        assert_eq!(normalize_path_prefix(None), "");
+        assert_eq!(normalize_path_prefix(Some("")), "");
+        assert_eq!(normalize_path_prefix(Some("/")), "");
+        assert_eq!(
+            build_rest_uri(false, "localhost:2333", "", "loadtracks"),
+            "http://localhost:2333/v3/loadtracks"
+        );
+    }
+
+    #[test]
+    fn update_player_sends_no_replace() {
+        let url = |no_replace| {
+            update_player_request(
+                &Client::new(),
+                build_rest_uri(false, "localhost:2333", "", "sessions/abc/players/1"),
+                no_replace,
+                &LavalinkUpdatePlayer::new(),
+            )
+            .build()
+            .unwrap()
+            .url()
+            .to_string()
+        };
+
+        assert_eq!(
+            url(true),
+            "http://localhost:2333/v3/sessions/abc/players/1?noReplace=true"
+        );
+        assert_eq!(
+            url(false),
+            "http://localhost:2333/v3/sessions/abc/players/1?noReplace=false"
+        );
+    }
+
+    fn exception(message: &str) -> LavalinkException {
+        LavalinkException {
+            message: Some(message.to_owned()),
+            severity: LavalinkSeverity::Common,
+            cause: "com.sedmelluq.discord.lavaplayer.tools.FriendlyException".to_owned(),
+        }
+    }
+
+    #[test]
+    fn region_locked_exception() {
+        assert!(exception("This video is not available in your country.").is_region_locked());
+        assert!(
+            exception("The uploader has not made this video available in your Region")
+                .is_region_locked()
+        );
+        assert!(!exception("This video is private.").is_region_locked());
+    }
+
+    #[test]
+    fn update_player_batches_the_fields() {
+        let mut player = LavalinkUpdatePlayer::new();
+        player
+            .encoded_track("track")
+            .position(1000)
+            .volume(50)
+            .paused(true);
+
+        let request = update_player_request(
+            &Client::new(),
+            build_rest_uri(false, "localhost:2333", "", "sessions/abc/players/1"),
+            false,
+            &player,
+        )
+        .build()
+        .unwrap();
+
+        let body: serde_json::Value =
+            serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap();
+
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "encodedTrack": "track",
+                "position": 1000,
+                "volume": 50,
+                "paused": true
+            })
+        );
+    }
+
+    #[test]
+    fn volume_update_is_clamped() {
+        assert_eq!(
+            serde_json::to_value(volume_update(150)).unwrap(),
+            serde_json::json!({ "volume": 150 })
+        );
+        assert_eq!(
+            serde_json::to_value(volume_update(5000)).unwrap(),
+            serde_json::json!({ "volume": 1000 })
+        );
+    }
+
+    /// Gets the URL of the track loading request.
+    fn track_load_url(identifier: &str) -> String {
+        track_load_request(
+            &Client::new(),
+            build_rest_uri(false, "localhost:2333", "", "loadtracks"),
+            identifier,
+        )
+        .build()
+        .unwrap()
+        .url()
+        .to_string()
+    }
+
+    #[test]
+    fn info_has_the_version_and_sources() {
+        let info: LavalinkInfo = serde_json::from_value(serde_json::json!({
+            "version": { "semver": "3.7.11", "major": 3, "minor": 7, "patch": 11 },
+            "sourceManagers": ["youtube", "soundcloud"]
+        }))
+        .unwrap();
+
+        assert_eq!(info.version.semver, "3.7.11");
+        assert_eq!(info.version.major, LAVALINK_API_VERSION);
+        assert_eq!(info.source_managers, ["youtube", "soundcloud"]);
+    }
+
+    #[test]
+    fn player_update_without_a_track() {
+        let message = r#"{"op":"playerUpdate","guildId":"1","state":{"time":1500467109,"connected":true,"ping":50}}"#;
+
+        assert!(matches!(
+            serde_json::from_str::<LavalinkInternalOp>(message)
+                .unwrap()
+                .op,
+            LavalinkOpType::PlayerUpdate
+        ));
+
+        let update: LavalinkPlayerUpdate = serde_json::from_str(message).unwrap();
+        assert_eq!(update.guild_id, "1");
+        assert_eq!(update.state.position, 0);
+        assert!(update.state.connected);
+    }
+
+    #[test]
+    fn update_session_disables_the_resuming_with_null() {
+        assert_eq!(
+            serde_json::to_value(LavalinkUpdateSession::default()).unwrap(),
+            serde_json::json!({ "resumingKey": null })
+        );
+        assert_eq!(
+            serde_json::to_value(LavalinkUpdateSession {
+                resuming_key: Some("abc".to_owned()),
+                timeout: Some(60),
+            })
+            .unwrap(),
+            serde_json::json!({ "resumingKey": "abc", "timeout": 60 })
+        );
+    }
+
+    #[test]
+    fn track_load_encodes_special_characters() {
+        assert_eq!(
+            track_load_url("ytsearch:AC/DC - Thunderstruck & more"),
+            "http://localhost:2333/v3/loadtracks?identifier=ytsearch%3AAC%2FDC+-+Thunderstruck+%26+more"
+        );
+        assert_eq!(
+            track_load_url("ytsearch:what? #1"),
+            "http://localhost:2333/v3/loadtracks?identifier=ytsearch%3Awhat%3F+%231"
+        );
+    }
+
+    #[test]
+    fn track_load_encodes_urls_with_query_parameters() {
+        assert_eq!(
+            track_load_url("https://www.youtube.com/watch?v=dQw4w9WgXcQ&list=RDdQw4w9WgXcQ"),
+            "http://localhost:2333/v3/loadtracks?identifier=https%3A%2F%2Fwww.youtube.com%2Fwatch%3Fv%3DdQw4w9WgXcQ%26list%3DRDdQw4w9WgXcQ"
+        );
+    }
+}
@@ -1,5 +1,9 @@
+use std::fmt::Debug;
+
 use serde::{Deserialize, Serialize};
 
+use super::REDACTED;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkErrorResponse {
@@ -24,6 +28,18 @@ pub struct LavalinkVoiceState {
     pub ping: i32,
 }
 
+impl Debug for LavalinkVoiceState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LavalinkVoiceState")
+            .field("token", &REDACTED)
+            .field("endpoint", &self.endpoint)
+            .field("session_id", &self.session_id)
+            .field("connected", &self.connected)
+            .field("ping", &self.ping)
+            .finish()
+    }
+}
+
 impl LavalinkVoiceState {
     pub fn new(token: &str, endpoint: &str, session_id: &str) -> Self {
         Self {
@@ -36,17 +52,24 @@ impl LavalinkVoiceState {
     }
 }
 
-#[derive(Clone, Serialize)]
+/// Changes sent to a Lavalink player, only the fields that are `Some` are sent, allowing them to be batched in one
+/// request.
+#[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkUpdatePlayer {
+    /// Track to play: `None` keeps the current one, `Some(None)` stops it and `Some(Some(track))` plays the track.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub encoded_track: Option<Option<String>>,
+    /// Identifier of the track to play, can't be sent with `encoded_track`.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub identifier: Option<String>,
+    /// Position of the track in milliseconds.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub position: Option<i32>,
+    /// Position in milliseconds where the track ends.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub end_time: Option<i32>,
+    /// Volume in percentage, from 0 to 1000.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub volume: Option<i32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -93,9 +116,16 @@ impl LavalinkUpdatePlayer {
 
         self
     }
+
+    /// Sets the volume, from `0` to `1000`, where `100` is the original volume.
+    pub fn volume(&mut self, volume: u16) -> &mut Self {
+        self.volume = Some(volume.into());
+
+        self
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkPlayer {
     pub guild_id: String,
@@ -105,7 +135,7 @@ pub struct LavalinkPlayer {
     pub voice: LavalinkVoiceState,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkTrack {
     pub encoded: String,
@@ -113,7 +143,7 @@ pub struct LavalinkTrack {
     pub info: LavalinkTrackInfo,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkTrackInfo {
     pub identifier: String,
@@ -125,9 +155,11 @@ pub struct LavalinkTrackInfo {
     pub title: String,
     pub uri: Option<String>,
     pub source_name: String,
+    #[serde(default)]
+    pub artwork_url: Option<String>,
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkTrackLoading {
     pub playlist_info: LavalinkPlaylistInfo,
@@ -136,7 +168,7 @@ pub struct LavalinkTrackLoading {
     pub load_type: LavalinkLoadResultType,
 }
 
-#[derive(Deserialize, PartialEq, Eq)]
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LavalinkLoadResultType {
     TrackLoaded,
@@ -146,14 +178,14 @@ pub enum LavalinkLoadResultType {
     LoadFailed,
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkPlaylistInfo {
     pub name: Option<String>,
     pub selected_track: Option<i32>,
 }
 
-#[derive(Deserialize)]
+#[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkException {
     pub message: Option<String>,
@@ -161,10 +193,53 @@ pub struct LavalinkException {
     pub cause: String,
 }
 
-#[derive(Deserialize, Debug, PartialEq, Eq)]
+impl LavalinkException {
+    /// Checks if the track exists but is blocked in the node's country or region, based on the source's message.
+    pub fn is_region_locked(&self) -> bool {
+        [self.message.as_deref().unwrap_or_default(), &self.cause]
+            .iter()
+            .map(|v| v.to_lowercase())
+            .any(|v| v.contains("country") || v.contains("region"))
+    }
+}
+
+#[derive(Clone, Deserialize, Debug, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LavalinkSeverity {
     Common,
     Suspicious,
     Fault,
 }
+
+/// Information about the Lavalink node, only with its version and sources.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LavalinkInfo {
+    #[serde(default)]
+    pub version: LavalinkVersion,
+    /// Names of the sources enabled in the node, like `youtube` or `spotify`.
+    #[serde(default)]
+    pub source_managers: Vec<String>,
+}
+
+/// Version of the Lavalink server.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LavalinkVersion {
+    /// Full version, like `3.7.11`.
+    pub semver: String,
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+/// Resuming configuration of the session, allowing the players to survive a reconnection.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LavalinkUpdateSession {
+    /// Key sent when reconnecting to resume the session, `None` disables the resuming.
+    pub resuming_key: Option<String>,
+    /// Seconds the node keeps the session after the connection drops.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<u32>,
+}
@@ -2,21 +2,64 @@ use serde::Deserialize;
 
 use super::rest::LavalinkException;
 
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkReadyEvent {
     pub resumed: bool,
     pub session_id: String,
 }
 
-#[derive(Deserialize)]
+/// State of a player, sent by the node every few seconds while it's connected.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LavalinkPlayerUpdate {
+    pub guild_id: String,
+    pub state: LavalinkPlayerState,
+}
+
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LavalinkPlayerState {
+    /// When the state was sent, as a UNIX timestamp in milliseconds.
+    pub time: i64,
+    /// Position of the track in milliseconds.
+    #[serde(default)]
+    pub position: i32,
+    /// If the player is connected to the voice channel.
+    pub connected: bool,
+    /// Latency to Discord's voice server in milliseconds, `-1` if not connected.
+    pub ping: i32,
+}
+
+/// Resource usage sent by the node every minute.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LavalinkStats {
+    /// Players created in the node.
+    pub players: usize,
+    /// Players playing a track.
+    pub playing_players: usize,
+    pub cpu: LavalinkCpuStats,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LavalinkCpuStats {
+    pub cores: u32,
+    /// Load of the whole system, from `0.0` to `1.0`.
+    pub system_load: f64,
+    /// Load of the Lavalink process, from `0.0` to `1.0`.
+    pub lavalink_load: f64,
+}
+
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkTrackStartEvent {
     pub guild_id: String,
     pub encoded_track: String,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkTrackEndEvent {
     pub guild_id: String,
@@ -24,7 +67,7 @@ pub struct LavalinkTrackEndEvent {
     pub reason: LavalinkTrackEndReason,
 }
 
-#[derive(Deserialize, PartialEq, Eq)]
+#[derive(Debug, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum LavalinkTrackEndReason {
     Finished,
@@ -34,16 +77,26 @@ pub enum LavalinkTrackEndReason {
     Cleanup,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkTrackExceptionEvent {
     pub encoded_track: String,
     pub exception: LavalinkException,
 }
 
-#[derive(Deserialize)]
+#[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct LavalinkTrackStuckEvent {
     pub encoded_track: String,
     pub threshold_ms: i32,
 }
+
+/// Discord closed the voice connection used by the player.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LavalinkWebSocketClosedEvent {
+    pub guild_id: String,
+    pub code: u16,
+    pub reason: String,
+    pub by_remote: bool,
+}
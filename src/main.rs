@@ -1,24 +1,37 @@
-use std::{collections::HashMap, env, process::exit, sync::Arc, time::Instant};
+use std::{
+    collections::{HashMap, HashSet},
+    env,
+    process::exit,
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use async_trait::async_trait;
-use config::load_configuration;
+use cache::{RollCache, TrackCache};
+use config::{load_configuration, parse_color};
 use dashmap::DashMap;
-use handler::{register_commands, AutoRemoverKey};
+use handler::{
+    register_commands, sweep_cooldowns, AutoRemoverKey, CooldownStore, PageStore, StateStore,
+};
 use hydrogen_i18n::I18n;
 use lavalink::LavalinkNodeInfo;
-use manager::HydrogenManager;
+use manager::{HydrogenManager, IdleAction, NodeCapacity};
 use parsers::{RollParser, TimeParser};
+use roll::{Error, Format};
 use serenity::{
     all::{
-        Client, CommandId, ComponentInteraction, GatewayIntents, Interaction, Message, Ready,
-        ShardId, UserId, VoiceServerUpdateEvent, VoiceState,
+        ChannelId, Client, CommandId, ComponentInteraction, GatewayIntents, GuildId, Interaction,
+        Message, Ready, ShardId, UserId, VoiceServerUpdateEvent, VoiceState,
     },
     client::{Context, EventHandler},
     gateway::ShardRunnerInfo,
     prelude::TypeMapKey,
 };
 use songbird::SerenityInit;
+use stats::UsageStats;
 use tokio::{
+    signal::ctrl_c,
+    spawn,
     sync::{Mutex, RwLock},
     task::JoinHandle,
 };
@@ -27,28 +40,81 @@ use tracing_subscriber::{
     fmt::layer, layer::SubscriberExt, registry, util::SubscriberInitExt, EnvFilter,
 };
 
-use crate::handler::{handle_command, handle_component};
+use crate::handler::{handle_command, handle_component, handle_prefix_command};
 
+mod cache;
 mod commands;
 mod components;
 mod config;
 mod handler;
+#[cfg(feature = "health")]
+mod health;
 mod lavalink;
 mod manager;
 mod parsers;
 mod player;
 mod roll;
+mod stats;
 mod utils;
 
 pub const HYDROGEN_PRIMARY_COLOR: i32 = 0x5865f2;
 pub const HYDROGEN_ERROR_COLOR: i32 = 0xf04747;
 pub const HYDROGEN_EMPTY_CHAT_TIMEOUT: u64 = 10;
 pub const HYDROGEN_QUEUE_LIMIT: usize = 1000;
-pub const HYDROGEN_SEARCH_PREFIX: &str = "ytsearch:";
+/// Tracks that can fail to load in a row before the queue stops, avoiding retrying a broken queue forever.
+pub const HYDROGEN_LOAD_FAILURE_LIMIT: usize = 5;
+/// Amount of the last tracks in the queue checked for duplicates when enqueueing.
+pub const HYDROGEN_DUPLICATE_SCAN_LIMIT: usize = 100;
+/// Default volume used by new players.
+pub const HYDROGEN_DEFAULT_VOLUME: u16 = 100;
+/// Maximum volume allowed to be set by the users.
+pub const HYDROGEN_MAX_VOLUME: u16 = 200;
 pub const HYDROGEN_WARNING_TIMEOUT: u64 = 10;
 pub const HYDROGEN_WARNING_PROBABILITY: f64 = 0.1;
 pub const HYDROGEN_COLOR: i32 = 0x009b60;
+/// Major version of the Lavalink API used by Hydrogen.
+pub const LAVALINK_API_VERSION: u32 = 3;
 pub const LAVALINK_CONNECTION_TIMEOUT: u64 = 5000;
+/// Maximum volume accepted by Lavalink.
+pub const LAVALINK_MAX_VOLUME: u16 = 1000;
+/// Delay in milliseconds before the first attempt to reconnect to a Lavalink node, doubled after each failure.
+pub const LAVALINK_RECONNECT_DELAY: u64 = 1000;
+/// Maximum delay in milliseconds between the attempts to reconnect to a Lavalink node.
+pub const LAVALINK_RECONNECT_MAX_DELAY: u64 = 30000;
+/// Default cooldown in milliseconds between components used by the same user.
+pub const HYDROGEN_COMPONENT_COOLDOWN: u64 = 1000;
+/// Interval in seconds between the removals of the expired component cooldowns.
+pub const HYDROGEN_COOLDOWN_SWEEP_INTERVAL: u64 = 60;
+/// Default maximum amount of loaded tracks kept in the cache.
+pub const HYDROGEN_TRACK_CACHE_SIZE: usize = 100;
+/// Default time in seconds the loaded tracks are kept in the cache.
+pub const HYDROGEN_TRACK_CACHE_TTL: u64 = 600;
+/// Maximum time in milliseconds to wait for Songbird to establish the voice connection.
+pub const HYDROGEN_VOICE_CONNECTION_TIMEOUT: u64 = 5000;
+/// Interval in milliseconds between each check of the voice connection.
+pub const HYDROGEN_VOICE_CONNECTION_INTERVAL: u64 = 100;
+/// Interval in milliseconds between each check of the requester's voice state while loading a query.
+pub const HYDROGEN_REQUESTER_CHECK_INTERVAL: u64 = 500;
+/// Time in seconds the users who requested the tracks are kept in the cache.
+pub const HYDROGEN_USER_CACHE_TTL: u64 = 300;
+/// Maximum amount of users whose last roll is kept to be rolled again.
+pub const HYDROGEN_LAST_ROLL_LIMIT: usize = 1000;
+/// Time in seconds the last roll of each user is kept to be rolled again.
+pub const HYDROGEN_LAST_ROLL_TTL: u64 = 3600;
+/// Maximum of characters in a message sent by the bot.
+pub const HYDROGEN_MESSAGE_LIMIT: usize = 2000;
+/// Maximum of characters in the description of an embed.
+pub const HYDROGEN_EMBED_DESCRIPTION_LIMIT: usize = 4096;
+/// Amount of tracks shown in each page of the queue.
+pub const HYDROGEN_QUEUE_PAGE_SIZE: usize = 10;
+/// Maximum amount of pages stored for the paginated responses.
+pub const HYDROGEN_PAGE_STATE_LIMIT: usize = 1000;
+/// Time in seconds a page is kept stored without being used.
+pub const HYDROGEN_PAGE_STATE_TIMEOUT: u64 = 300;
+/// Default time in seconds the command responses are kept before being deleted.
+pub const HYDROGEN_RESPONSE_DELETE_DELAY: u64 = 120;
+/// Maximum time in seconds the command responses can be kept, as Discord invalidates the interaction after 15 minutes.
+pub const HYDROGEN_RESPONSE_DELETE_MAX_DELAY: u64 = 840;
 /// The public instance ID.
 pub const HYDROGEN_PUBLIC_INSTANCE_ID: u64 = 1128087591179268116;
 
@@ -69,6 +135,9 @@ pub static HYDROGEN_NAME: &str = "Hydrogen";
 /// Default language file already loaded in the binary.
 pub static HYDROGEN_DEFAULT_LANGUAGE: &str = include_str!("../assets/langs/en-US.json");
 
+/// Minimal English translation used when no default language is loaded, only with the generic texts and the errors.
+pub static HYDROGEN_FALLBACK_LANGUAGE: &str = include_str!("../assets/fallback-language.json");
+
 /// The public instance and other roll bots IDs.
 pub static OTHER_ROLL_BOTS: [u64; 1] = [
     // Rollem bot ID.
@@ -90,8 +159,50 @@ struct HydrogenContext {
 
     /// The responses from the components.
     pub components_responses: Arc<DashMap<AutoRemoverKey, (JoinHandle<()>, ComponentInteraction)>>,
-    /// Whether this is the public instance.
-    pub public_instance: bool,
+
+    /// The page of the queue each user is viewing.
+    pub queue_pages: Arc<PageStore>,
+
+    /// The query each user asked to play that is already in the queue, waiting for the confirmation.
+    pub pending_duplicates: Arc<StateStore<String>>,
+
+    /// The last time each user used a component in a guild.
+    pub components_cooldown: Arc<CooldownStore>,
+    /// The cooldown between components used by the same user.
+    pub component_cooldown: Duration,
+    /// How long the command responses are kept before being deleted, `None` disables it.
+    pub response_delete_delay: Option<Duration>,
+    /// When the instance ends, sometimes shown before the commands, `None` disables it.
+    pub sunset_notice: Option<u64>,
+
+    /// The color used by the embeds.
+    pub primary_color: i32,
+    /// The color used by the error embeds.
+    pub error_color: i32,
+
+    /// Cache for the tracks loaded by the players.
+    pub track_cache: Arc<TrackCache>,
+
+    /// How long a player can stay paused before leaving, `None` disables it.
+    pub auto_leave_paused: Option<Duration>,
+
+    /// What the players do when they're alone in the voice channel.
+    pub idle_action: IdleAction,
+
+    /// Maximum of tracks in the queue requested by the same user, `None` disables it.
+    pub user_queue_limit: Option<usize>,
+
+    /// Limits of each Lavalink node, checked before creating a player.
+    pub node_capacity: NodeCapacity,
+
+    /// The last roll of each user, used by `/roll again`.
+    pub last_rolls: Arc<RollCache>,
+
+    /// Channels of each guild where the rolls from messages are disabled, changed by `/settings roll_here`.
+    pub disabled_roll_channels: Arc<DashMap<GuildId, HashSet<ChannelId>>>,
+
+    /// How many times each command and component was used and how long they took.
+    pub usage_stats: Arc<UsageStats>,
 }
 
 #[derive(Clone)]
@@ -102,6 +213,8 @@ struct HydrogenHandler {
     other_roll_bots: Vec<u64>,
     /// If the bot should force enable auto-roll from messages.
     force_roll: bool,
+    /// The prefix of the text commands, disabled if `None`.
+    command_prefix: Option<String>,
 }
 
 #[async_trait]
@@ -131,6 +244,12 @@ impl EventHandler for HydrogenHandler {
             ctx.cache.clone(),
             ctx.http.clone(),
             self.context.i18n.clone(),
+            self.context.primary_color,
+            self.context.track_cache.clone(),
+            self.context.auto_leave_paused,
+            self.context.idle_action,
+            self.context.user_queue_limit,
+            self.context.node_capacity,
         );
         *self.context.manager.write().await = Some(manager.clone());
         debug!("(ready): HydrogenManager initialized");
@@ -183,6 +302,10 @@ impl EventHandler for HydrogenHandler {
             Interaction::Command(command) => {
                 handle_command(&self.context, &ctx, &command).await;
 
+                self.context
+                    .usage_stats
+                    .record(&command.data.name, timer.elapsed());
+
                 info!(
                     "(interaction_create): command '{}' executed in {}ms",
                     command.data.name,
@@ -192,6 +315,10 @@ impl EventHandler for HydrogenHandler {
             Interaction::Component(component) => {
                 handle_component(&self.context, &ctx, &component).await;
 
+                self.context
+                    .usage_stats
+                    .record(&component.data.custom_id, timer.elapsed());
+
                 info!(
                     "(interaction_create): component '{}' executed in {}ms",
                     component.data.custom_id,
@@ -261,6 +388,27 @@ impl EventHandler for HydrogenHandler {
             return;
         }
 
+        // Execute the text command, if enabled.
+        if let Some(prefix) = &self.command_prefix {
+            if handle_prefix_command(&self.context, &ctx, &message, prefix).await {
+                info!("(message): processed in {}ms", timer.elapsed().as_millis());
+                return;
+            }
+        }
+
+        // Ignore messages in the channels where the rolls are disabled, even if forced.
+        if let Some(guild_id) = message.guild_id {
+            if self
+                .context
+                .disabled_roll_channels
+                .get(&guild_id)
+                .is_some_and(|channels| channels.contains(&message.channel_id))
+            {
+                debug!("(message): rolls disabled in the channel, ignored");
+                return;
+            }
+        }
+
         // Ignore messages from other roll bots.
         if !self.force_roll {
             if let Some(guild_id) = message.guild_id {
@@ -284,20 +432,48 @@ impl EventHandler for HydrogenHandler {
 
         // Send message to the roll parser.
         if let Some(params) = self.context.roll_parser.evaluate(&message.content) {
-            match params.roll() {
+            // The rolls from messages use the guild's locale.
+            let locale = message
+                .guild_id
+                .and_then(|guild_id| ctx.cache.guild(guild_id))
+                .map(|guild| guild.preferred_locale.clone())
+                .unwrap_or_default();
+
+            let content = match params.roll() {
                 Ok(result) => {
-                    if let Err(e) = message.reply_ping(ctx, result.to_string()).await {
-                        warn!("(message): cannot send roll result: {}", e);
-                    }
+                    self.context.last_rolls.insert(message.author.id, params);
+
+                    Some(result.to_limited_string(
+                        Format::Detailed,
+                        HYDROGEN_MESSAGE_LIMIT,
+                        |count| {
+                            self.context
+                                .i18n
+                                .translate(&locale, "roll", "truncated")
+                                .replace("{count}", &count.to_string())
+                        },
+                    ))
                 }
+                Err(Error::DivideByZero) => Some(self.context.i18n.translate(
+                    &locale,
+                    "roll",
+                    "divide_by_zero",
+                )),
                 Err(e) => {
                     warn!(
                         "(message): cannot roll for user {}: {}",
                         message.author.id, e
                     );
+                    None
                 }
             };
 
+            if let Some(content) = content {
+                if let Err(e) = message.reply_ping(ctx, content).await {
+                    warn!("(message): cannot send roll result: {}", e);
+                }
+            }
+
             info!("(message): processed in {}ms", timer.elapsed().as_millis());
         } else {
             debug!("(message): ignored");
@@ -306,23 +482,91 @@ impl EventHandler for HydrogenHandler {
 }
 
 #[cfg(not(feature = "builtin-language"))]
-/// Create a new i18n instance.
+/// Create a new i18n instance with the fallback language, replaced by the default one when the language files are loaded.
 #[inline]
 fn new_i18n() -> I18n {
-    I18n::new()
+    fallback_i18n()
 }
 
 #[cfg(feature = "builtin-language")]
 /// Create a new i18n instance with default language if can be parsed.
 #[inline]
 fn new_i18n() -> I18n {
-    if let Ok(default_language) = hydrogen_i18n::serde_json::from_str(HYDROGEN_DEFAULT_LANGUAGE) {
-        I18n::new_with_default(default_language)
-    } else {
-        I18n::new()
+    match hydrogen_i18n::serde_json::from_str(HYDROGEN_DEFAULT_LANGUAGE) {
+        Ok(default_language) => I18n::new_with_default(default_language),
+        Err(e) => {
+            warn!("cannot parse the builtin default language: {}", e);
+            fallback_i18n()
+        }
+    }
+}
+
+/// Create a new i18n instance with the fallback language, avoiding responding with translation keys.
+fn fallback_i18n() -> I18n {
+    match hydrogen_i18n::serde_json::from_str(HYDROGEN_FALLBACK_LANGUAGE) {
+        Ok(fallback_language) => I18n::new_with_default(fallback_language),
+        Err(e) => {
+            warn!("cannot parse the fallback language: {}", e);
+            I18n::new()
+        }
     }
 }
 
+/// Destroy all players and stop the shards when receiving Ctrl+C.
+fn start_shutdown_handler(client: &Client, manager: Arc<RwLock<Option<HydrogenManager>>>) {
+    let shard_manager = client.shard_manager.clone();
+    spawn(async move {
+        if let Err(e) = ctrl_c().await {
+            error!("cannot listen for the shutdown signal: {}", e);
+            return;
+        }
+
+        info!("shutting down...");
+
+        if let Some(manager) = manager.read().await.clone() {
+            for (guild_id, result) in manager.destroy_all().await {
+                if let Err(e) = result {
+                    warn!("cannot destroy the player in the guild {}: {}", guild_id, e);
+                }
+            }
+
+            manager.close_lavalink().await;
+        }
+
+        shard_manager.shutdown_all().await;
+    });
+}
+
+#[cfg(feature = "health")]
+/// Start the readiness endpoint in the background.
+fn start_health(
+    address: String,
+    token: Option<String>,
+    client: &Client,
+    manager: Arc<RwLock<Option<HydrogenManager>>>,
+    usage_stats: Arc<UsageStats>,
+) {
+    let runners = client.shard_manager.runners.clone();
+    tokio::spawn(async move {
+        health::serve(address, token, runners, manager, usage_stats).await;
+    });
+}
+
+#[cfg(not(feature = "health"))]
+/// Warn that the readiness endpoint isn't available in this build.
+fn start_health(
+    address: String,
+    _: Option<String>,
+    _: &Client,
+    _: Arc<RwLock<Option<HydrogenManager>>>,
+    _: Arc<UsageStats>,
+) {
+    warn!(
+        "cannot listen on {}: the readiness endpoint requires the 'health' feature",
+        address
+    );
+}
+
 /// Executable entrypoint.
 #[tokio::main]
 async fn main() {
@@ -338,6 +582,14 @@ async fn main() {
     // Load configuration from file or environment.
     let mut config = load_configuration().or_from_env();
 
+    // Refuse to start with an incomplete configuration, showing everything that needs to be fixed.
+    if let Err(errors) = config.validate() {
+        for e in errors {
+            error!("invalid configuration: {}", e);
+        }
+        exit(1);
+    }
+
     if config.public_instance.unwrap_or_default() {
         warn!("you are running this instance as a public instance");
     }
@@ -382,34 +634,133 @@ async fn main() {
     let lavalink_nodes = config
         .lavalink
         .take()
-        .unwrap()
+        .expect("validated by Config::validate")
         .into_iter()
         .map(LavalinkNodeInfo::from)
         .collect();
 
+    // Parse the embed colors.
+    let primary_color = config
+        .primary_color
+        .as_deref()
+        .and_then(|color| {
+            let parsed = parse_color(color);
+            if parsed.is_none() {
+                warn!("invalid primary color '{}', using the default", color);
+            }
+            parsed
+        })
+        .unwrap_or(HYDROGEN_PRIMARY_COLOR);
+
+    let error_color = config
+        .error_color
+        .as_deref()
+        .and_then(|color| {
+            let parsed = parse_color(color);
+            if parsed.is_none() {
+                warn!("invalid error color '{}', using the default", color);
+            }
+            parsed
+        })
+        .unwrap_or(HYDROGEN_ERROR_COLOR);
+
+    // Parse what the players do when they're alone.
+    let idle_action = config
+        .idle_action
+        .as_deref()
+        .and_then(|action| {
+            let parsed = IdleAction::from_name(action);
+            if parsed.is_none() {
+                warn!("invalid idle action '{}', using the default", action);
+            }
+            parsed
+        })
+        .unwrap_or_default();
+
+    // Get how long the command responses are kept, limited by the interaction's lifetime.
+    let response_delete_delay = config.auto_delete_responses.unwrap_or_default().then(|| {
+        let delay = config
+            .response_delete_delay
+            .unwrap_or(HYDROGEN_RESPONSE_DELETE_DELAY);
+        if delay > HYDROGEN_RESPONSE_DELETE_MAX_DELAY {
+            warn!(
+                "the response delete delay is longer than {} seconds, using it instead",
+                HYDROGEN_RESPONSE_DELETE_MAX_DELAY
+            );
+        }
+        Duration::from_secs(delay.min(HYDROGEN_RESPONSE_DELETE_MAX_DELAY))
+    });
+
+    // Get when the instance ends, shown only if the notice is enabled.
+    let sunset_notice = if config.show_sunset_notice.unwrap_or_default() {
+        if config.sunset_time.is_none() {
+            warn!("the sunset notice is enabled, but the sunset time isn't set");
+        }
+        config.sunset_time
+    } else {
+        None
+    };
+
     let mut other_roll_bots = Vec::from(OTHER_ROLL_BOTS);
     if !config.public_instance.unwrap_or_default() {
         other_roll_bots.push(HYDROGEN_PUBLIC_INSTANCE_ID);
     }
 
     // Initialize HydrogenHandler.
+    let manager = Arc::new(RwLock::new(None));
+    let usage_stats = Arc::new(UsageStats::default());
     let app = HydrogenHandler {
         context: HydrogenContext {
-            manager: Arc::new(RwLock::new(None)),
+            manager: manager.clone(),
             commands_id: Arc::new(RwLock::new(HashMap::new())),
             i18n: Arc::new(i18n),
             components_responses: Arc::new(DashMap::new()),
-            public_instance: config.public_instance.unwrap_or_default(),
+            queue_pages: Arc::new(DashMap::new()),
+            pending_duplicates: Arc::new(DashMap::new()),
+            components_cooldown: Arc::new(DashMap::new()),
+            component_cooldown: Duration::from_millis(
+                config
+                    .component_cooldown
+                    .unwrap_or(HYDROGEN_COMPONENT_COOLDOWN),
+            ),
+            response_delete_delay,
+            sunset_notice,
+            primary_color,
+            error_color,
+            track_cache: Arc::new(TrackCache::new(
+                config.track_cache_size.unwrap_or(HYDROGEN_TRACK_CACHE_SIZE),
+                Duration::from_secs(config.track_cache_ttl.unwrap_or(HYDROGEN_TRACK_CACHE_TTL)),
+            )),
+            auto_leave_paused: config.auto_leave_paused_secs.map(Duration::from_secs),
+            idle_action,
+            user_queue_limit: config.user_queue_limit,
+            node_capacity: NodeCapacity {
+                max_players: config.max_players_per_node,
+                max_cpu_load: config.max_node_cpu_load,
+            },
+            last_rolls: Arc::new(RollCache::bounded(
+                HYDROGEN_LAST_ROLL_LIMIT,
+                Duration::from_secs(HYDROGEN_LAST_ROLL_TTL),
+            )),
+            disabled_roll_channels: Arc::new(DashMap::new()),
+            usage_stats: usage_stats.clone(),
             time_parsers,
             roll_parser,
         },
         lavalink_nodes: Arc::new(lavalink_nodes),
         other_roll_bots,
         force_roll: config.force_roll.unwrap_or_default(),
+        command_prefix: config.command_prefix.filter(|prefix| !prefix.is_empty()),
     };
 
+    // Remove the cooldowns of the users who stopped using the player controls.
+    spawn(sweep_cooldowns(
+        app.context.components_cooldown.clone(),
+        app.context.component_cooldown,
+    ));
+
     let mut client = Client::builder(
-        &config.discord_token.unwrap(),
+        &config.discord_token.expect("validated by Config::validate"),
         GatewayIntents::GUILDS
             | GatewayIntents::GUILD_VOICE_STATES
             | GatewayIntents::MESSAGE_CONTENT
@@ -426,5 +777,19 @@ async fn main() {
         .await
         .insert::<ShardManagerRunners>(client.shard_manager.runners.clone());
 
+    // Leave the voice channels before shutting down.
+    start_shutdown_handler(&client, manager.clone());
+
+    // Start the readiness endpoint.
+    if let Some(health_address) = config.health_address {
+        start_health(
+            health_address,
+            config.health_token,
+            &client,
+            manager,
+            usage_stats,
+        );
+    }
+
     client.start().await.expect("cannot start client");
 }
@@ -1,6 +1,7 @@
 use std::{
     collections::HashMap,
     fmt::Display,
+    future::Future,
     process::exit,
     result,
     sync::{
@@ -11,39 +12,49 @@ use std::{
 };
 
 use async_trait::async_trait;
+use dashmap::DashMap;
+use futures::future::join_all;
 use hydrogen_i18n::I18n;
 use serenity::{
     all::{
-        ButtonStyle, ChannelId, ChannelType, GuildId, MessageId, ReactionType, UserId,
-        VoiceServerUpdateEvent, VoiceState,
+        ButtonStyle, Channel, ChannelId, ChannelType, GuildChannel, GuildId, MessageId,
+        ReactionType, UserId, VoiceServerUpdateEvent, VoiceState,
     },
     builder::{
         CreateActionRow, CreateButton, CreateEmbed, CreateEmbedAuthor, CreateEmbedFooter,
-        CreateMessage, EditMessage,
+        CreateMessage, EditMessage, EditVoiceState,
     },
     client::Cache,
     http::{CacheHttp, Http},
 };
 use songbird::Songbird;
-use tokio::{spawn, sync::RwLock, task::JoinHandle, time::sleep};
+use tokio::{select, spawn, sync::RwLock, task::JoinHandle, time::sleep};
 use tracing::{debug, error, info, warn};
 
 use crate::{
+    cache::{CachedUser, TrackCache, UserCache},
     lavalink::{
+        self,
         websocket::{
-            LavalinkTrackEndEvent, LavalinkTrackEndReason, LavalinkTrackExceptionEvent,
-            LavalinkTrackStartEvent, LavalinkTrackStuckEvent,
+            LavalinkStats, LavalinkTrackEndEvent, LavalinkTrackEndReason,
+            LavalinkTrackExceptionEvent, LavalinkTrackStartEvent, LavalinkTrackStuckEvent,
+            LavalinkWebSocketClosedEvent,
         },
-        Lavalink, LavalinkError, LavalinkHandler, LavalinkNodeInfo,
+        Lavalink, LavalinkConnection, LavalinkError, LavalinkHandler, LavalinkNodeInfo,
     },
     player::{
-        HydrogenMusic, HydrogenPlayCommand, HydrogenPlayer, HydrogenPlayerError,
-        HydrogenSeekCommand, LoopType,
+        HydrogenMusic, HydrogenNowPlaying, HydrogenPlayCommand, HydrogenPlayer,
+        HydrogenPlayerError, HydrogenQueueDuration, HydrogenSeekCommand, LoopType, SearchSource,
     },
-    HYDROGEN_EMPTY_CHAT_TIMEOUT, HYDROGEN_LOGO_URL, HYDROGEN_PRIMARY_COLOR,
+    utils::artwork_url,
+    HYDROGEN_DEFAULT_VOLUME, HYDROGEN_EMPTY_CHAT_TIMEOUT, HYDROGEN_LOGO_URL,
+    HYDROGEN_REQUESTER_CHECK_INTERVAL, HYDROGEN_USER_CACHE_TTL, LAVALINK_API_VERSION,
 };
+#[cfg(feature = "health")]
+use crate::{lavalink::LavalinkNodeSnapshot, player::HydrogenPlayerSnapshot};
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum HydrogenManagerError {
     Lavalink(LavalinkError),
     Serenity(serenity::Error),
@@ -53,6 +64,9 @@ pub enum HydrogenManagerError {
     GuildIdMissing,
     GuildChannelNotFound,
     PlayerNotFound,
+    RequesterLeft,
+    DuplicateTrack,
+    NodesBusy,
 }
 
 impl Display for HydrogenManagerError {
@@ -68,12 +82,70 @@ impl Display for HydrogenManagerError {
             Self::GuildIdMissing => write!(f, "GuildId missing"),
             Self::GuildChannelNotFound => write!(f, "GuildChannel not found"),
             Self::PlayerNotFound => write!(f, "music player not found"),
+            Self::RequesterLeft => write!(f, "requester left the voice channel"),
+            Self::DuplicateTrack => write!(f, "track already in the queue"),
+            Self::NodesBusy => write!(f, "every lavalink node is at capacity"),
         }
     }
 }
 
 pub type Result<T> = result::Result<T, HydrogenManagerError>;
 
+/// Playback state of a guild, used by the commands and components.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaybackState {
+    /// The guild doesn't have a player.
+    NoPlayer,
+    /// The player doesn't have a track to play.
+    Idle,
+    Playing,
+    Paused,
+}
+
+/// What the player does when everyone else leaves its voice channel.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IdleAction {
+    /// Leave the voice channel after a timeout.
+    #[default]
+    Leave,
+    /// Pause the player, resuming it when someone joins again.
+    Pause,
+}
+
+impl IdleAction {
+    /// Gets the action from the name used in the config.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "leave" => Some(Self::Leave),
+            "pause" => Some(Self::Pause),
+            _ => None,
+        }
+    }
+}
+
+/// Limits of each Lavalink node, new players aren't created when every node reaches them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct NodeCapacity {
+    /// Maximum of players in the same node, `None` disables it.
+    pub max_players: Option<usize>,
+    /// Maximum CPU load of the Lavalink process in percent, `None` disables it.
+    pub max_cpu_load: Option<u8>,
+}
+
+impl NodeCapacity {
+    /// Checks if the node can't receive more players.
+    ///
+    /// `players` is the amount of players created by this manager in the node, as the stats are only sent every minute.
+    fn is_full(&self, players: usize, stats: Option<&LavalinkStats>) -> bool {
+        let players = players.max(stats.map(|v| v.players).unwrap_or_default());
+
+        self.max_players.is_some_and(|max| players >= max)
+            || self.max_cpu_load.is_some_and(|max| {
+                stats.is_some_and(|v| v.cpu.lavalink_load * 100.0 >= f64::from(max))
+            })
+    }
+}
+
 #[derive(Clone, PartialEq, Eq)]
 enum HydrogenPlayerState {
     Nothing,
@@ -81,6 +153,66 @@ enum HydrogenPlayerState {
     Thinking,
 }
 
+/// What to do with the queue when a track ends.
+#[derive(Debug, PartialEq, Eq)]
+enum TrackEndAction {
+    /// Go to the next track in the queue.
+    Next,
+    /// Keep the queue as it is.
+    Keep,
+}
+
+impl From<&LavalinkTrackEndReason> for TrackEndAction {
+    fn from(reason: &LavalinkTrackEndReason) -> Self {
+        match reason {
+            // The track played until the end.
+            LavalinkTrackEndReason::Finished => Self::Next,
+            // The track can't be played, skip it instead of stopping the queue, even when looping it.
+            LavalinkTrackEndReason::LoadFailed => Self::Next,
+            // The player was stopped on purpose, there's nothing to play.
+            LavalinkTrackEndReason::Stopped => Self::Keep,
+            // Skip, prev, jump and play already moved the queue before replacing the track.
+            LavalinkTrackEndReason::Replaced => Self::Keep,
+            // Lavalink removed the player, the manager's state is removed by `destroy`.
+            LavalinkTrackEndReason::Cleanup => Self::Keep,
+        }
+    }
+}
+
+/// What to do with the player when Discord closes its voice connection.
+#[derive(Debug, PartialEq, Eq)]
+enum VoiceClosedAction {
+    /// Join the voice channel again, creating a new voice session.
+    Reconnect,
+    /// Destroy the player, telling the users why it stopped.
+    Destroy,
+    /// Nothing to do, the connection was closed on purpose or is handled by the voice state updates.
+    Ignore,
+}
+
+impl From<&LavalinkWebSocketClosedEvent> for VoiceClosedAction {
+    fn from(event: &LavalinkWebSocketClosedEvent) -> Self {
+        // Lavalink closed the connection itself, like when the player is destroyed.
+        if !event.by_remote {
+            return Self::Ignore;
+        }
+
+        match event.code {
+            // Normal closure.
+            1000 => Self::Ignore,
+            // Session no longer valid, session timeout and voice server crashed.
+            4006 | 4009 | 4015 => Self::Reconnect,
+            // Disconnected from the channel, the player is destroyed by the voice state update.
+            4014 => Self::Ignore,
+            _ => Self::Destroy,
+        }
+    }
+}
+
+/// Manages the players of every guild and the Lavalink nodes used by them.
+///
+/// Cloning is cheap: the clones share the players, the nodes, the caches and the guild settings, only the configuration
+/// given to [`Self::new`] is copied. The Lavalink handler and the destroy tasks rely on this to reach the same players.
 #[derive(Clone)]
 pub struct HydrogenManager {
     cache: Arc<Cache>,
@@ -88,19 +220,78 @@ pub struct HydrogenManager {
     http: Arc<Http>,
     i18n: Arc<I18n>,
     lavalink: Arc<RwLock<Vec<Lavalink>>>,
+    /// HTTP client shared by the Lavalink nodes, reusing the same connection pool.
+    lavalink_http: reqwest::Client,
     load_balancer: Arc<AtomicUsize>,
     message: Arc<RwLock<HashMap<GuildId, MessageId>>>,
-    player: Arc<RwLock<HashMap<GuildId, HydrogenPlayer>>>,
+    player: Arc<DashMap<GuildId, HydrogenPlayer>>,
+    /// Last volume set in each guild, kept across player reconnections.
+    volume: Arc<DashMap<GuildId, u16>>,
+    /// Whether each guild wants the now playing message, enabled by default.
+    announce: Arc<DashMap<GuildId, bool>>,
+    /// Source used by each guild to search the queries that aren't URLs.
+    search_source: Arc<DashMap<GuildId, SearchSource>>,
+    /// Whether each guild removes the tracks of the users who leave the voice channel, disabled by default.
+    remove_on_leave: Arc<DashMap<GuildId, bool>>,
+    /// Whether each guild asks before enqueueing a track already in the queue, disabled by default.
+    warn_duplicates: Arc<DashMap<GuildId, bool>>,
+    /// Loop type applied to the new players of each guild.
+    default_loop: Arc<DashMap<GuildId, LoopType>>,
+    /// The color used by the player messages.
+    primary_color: i32,
+    /// Cache for the tracks loaded by the players.
+    track_cache: Arc<TrackCache>,
+    /// Cache for the users shown in the player messages.
+    user_cache: Arc<UserCache>,
+    /// Tasks destroying the players paused for too long.
+    pause_destroy_handle: Arc<DashMap<GuildId, JoinHandle<()>>>,
+    /// How long a player can stay paused before being destroyed, `None` disables it.
+    auto_leave_paused: Option<Duration>,
+    /// What the players do when they're alone in the voice channel.
+    idle_action: IdleAction,
+    /// Maximum of tracks in the queue requested by the same user, `None` disables it.
+    user_queue_limit: Option<usize>,
+    /// Limits of each Lavalink node, checked before creating a player.
+    node_capacity: NodeCapacity,
+    /// Guilds whose players were paused because they were alone, resumed when someone joins again.
+    idle_paused: Arc<DashMap<GuildId, ()>>,
 }
 
 impl HydrogenManager {
-    pub fn new(cache: Arc<Cache>, http: Arc<Http>, i18n: Arc<I18n>) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        cache: Arc<Cache>,
+        http: Arc<Http>,
+        i18n: Arc<I18n>,
+        primary_color: i32,
+        track_cache: Arc<TrackCache>,
+        auto_leave_paused: Option<Duration>,
+        idle_action: IdleAction,
+        user_queue_limit: Option<usize>,
+        node_capacity: NodeCapacity,
+    ) -> Self {
         Self {
             lavalink: Arc::new(RwLock::new(Vec::new())),
+            lavalink_http: lavalink::http_client(),
             destroy_handle: Arc::new(RwLock::new(HashMap::new())),
             load_balancer: Arc::new(AtomicUsize::new(0)),
             message: Arc::new(RwLock::new(HashMap::new())),
-            player: Arc::new(RwLock::new(HashMap::new())),
+            player: Arc::new(DashMap::new()),
+            volume: Arc::new(DashMap::new()),
+            announce: Arc::new(DashMap::new()),
+            search_source: Arc::new(DashMap::new()),
+            remove_on_leave: Arc::new(DashMap::new()),
+            warn_duplicates: Arc::new(DashMap::new()),
+            default_loop: Arc::new(DashMap::new()),
+            primary_color,
+            track_cache,
+            user_cache: Arc::new(UserCache::new(Duration::from_secs(HYDROGEN_USER_CACHE_TTL))),
+            pause_destroy_handle: Arc::new(DashMap::new()),
+            auto_leave_paused,
+            idle_action,
+            user_queue_limit,
+            node_capacity,
+            idle_paused: Arc::new(DashMap::new()),
             cache,
             http,
             i18n,
@@ -110,7 +301,7 @@ impl HydrogenManager {
     pub async fn connect_lavalink(&self, node: LavalinkNodeInfo) -> Result<()> {
         let mut lavalink_vector = self.lavalink.write().await;
         let user_id = self.cache.current_user().id.get();
-        let lavalink = Lavalink::connect(node, user_id, self.clone())
+        let lavalink = Lavalink::connect(node, user_id, self.lavalink_http.clone(), self.clone())
             .await
             .map_err(HydrogenManagerError::Lavalink)?;
         lavalink_vector.push(lavalink);
@@ -137,6 +328,31 @@ impl HydrogenManager {
         index
     }
 
+    /// Gets the first node that isn't full, starting from the one chosen by the load balancer.
+    async fn available_node(&self, nodes: &[Lavalink], start: usize) -> Result<Lavalink> {
+        if nodes.is_empty() {
+            return Err(HydrogenManagerError::LavalinkNotConnected);
+        }
+
+        for offset in 0..nodes.len() {
+            let node = &nodes[(start + offset) % nodes.len()];
+            let players = self
+                .player
+                .iter()
+                .filter(|player| player.lavalink() == *node)
+                .count();
+
+            if !self
+                .node_capacity
+                .is_full(players, node.stats().await.as_ref())
+            {
+                return Ok(node.clone());
+            }
+        }
+
+        Err(HydrogenManagerError::NodesBusy)
+    }
+
     pub async fn init(
         &self,
         guild_id: GuildId,
@@ -155,15 +371,11 @@ impl HydrogenManager {
                 .cloned()
                 .ok_or(HydrogenManagerError::VoiceManagerNotConnected)?;
 
-            let mut players = self.player.write().await;
             let lavalink_nodes = self.lavalink.read().await;
 
             let lavalink_index = self.increment_load_balancer().await;
 
-            let lavalink = lavalink_nodes
-                .get(lavalink_index)
-                .cloned()
-                .ok_or(HydrogenManagerError::LavalinkNotConnected)?;
+            let lavalink = self.available_node(&lavalink_nodes, lavalink_index).await?;
             let player = HydrogenPlayer::new(
                 lavalink,
                 guild_id,
@@ -171,9 +383,14 @@ impl HydrogenManager {
                 connection_info.into(),
                 text_channel_id,
                 guild_locale,
+                self.get_volume(guild_id),
+                self.track_cache.clone(),
             );
 
-            players.insert(guild_id, player.clone());
+            // Apply the guild's loop type before the first track is enqueued.
+            player.set_loop_type(self.get_default_loop(guild_id)).await;
+
+            self.player.insert(guild_id, player.clone());
 
             player
         };
@@ -191,68 +408,162 @@ impl HydrogenManager {
         requester_id: UserId,
         voice_manager: Arc<Songbird>,
         text_channel_id: ChannelId,
+        allow_duplicate: bool,
     ) -> Result<HydrogenPlayCommand> {
-        let player_option = {
-            let option = self.player.read().await;
-            option.get(&guild_id).cloned()
+        let (player, created) = match self.get_player(guild_id) {
+            Some(player) => (player, false),
+            None => (
+                self.init(guild_id, guild_locale, voice_manager, text_channel_id)
+                    .await?,
+                true,
+            ),
+        };
+
+        // Show that the query is loading, replaced by the now playing message when it finishes.
+        self.update_status_message(
+            guild_id,
+            &player,
+            &self
+                .i18n
+                .translate(&player.guild_locale(), "player", "loading"),
+        )
+        .await;
+
+        // Load the query, giving up if the requester leaves the voice channel before it finishes.
+        let voice_channel_id = player.connection.read().await.channel_id;
+        let Some(musics) = load_while_present(
+            player.load(music, self.get_search_source(guild_id)),
+            || self.is_in_voice_channel(guild_id, requester_id, voice_channel_id),
+            Duration::from_millis(HYDROGEN_REQUESTER_CHECK_INTERVAL),
+        )
+        .await
+        else {
+            // Don't keep a player created only for this request.
+            if created {
+                if let Err(e) = self.destroy(guild_id).await {
+                    warn!("cannot destroy the player in the guild {}: {}", guild_id, e);
+                }
+            } else {
+                self.update_now_playing(guild_id).await;
+            }
+
+            return Err(HydrogenManagerError::RequesterLeft);
         };
 
-        if let Some(player) = player_option {
-            return player
-                .play(music, requester_id)
+        let result = match musics {
+            Ok(musics)
+                if !allow_duplicate
+                    && self.get_warn_duplicates(guild_id)
+                    && player.contains_duplicate(&musics).await =>
+            {
+                Err(HydrogenManagerError::DuplicateTrack)
+            }
+            Ok(musics) => player
+                .play(musics, requester_id, self.user_queue_limit)
                 .await
-                .map_err(HydrogenManagerError::Player);
-        }
+                .map_err(HydrogenManagerError::Player),
+            Err(e) => Err(HydrogenManagerError::Player(e)),
+        };
 
-        let player = self
-            .init(guild_id, guild_locale, voice_manager, text_channel_id)
-            .await?;
+        // Replace the loading message, even if the load failed.
+        self.update_now_playing(guild_id).await;
 
-        player
-            .play(music, requester_id)
-            .await
-            .map_err(HydrogenManagerError::Player)
+        result
+    }
+
+    /// Returns if the user is in the voice channel, or in any voice channel if it's unknown.
+    fn is_in_voice_channel(
+        &self,
+        guild_id: GuildId,
+        user_id: UserId,
+        channel_id: Option<songbird::id::ChannelId>,
+    ) -> bool {
+        let Some(user_channel_id) = self
+            .cache
+            .guild(guild_id)
+            .and_then(|guild| guild.voice_states.get(&user_id)?.channel_id)
+        else {
+            return false;
+        };
+
+        match channel_id {
+            Some(channel_id) => channel_id == user_channel_id.into(),
+            None => true,
+        }
+    }
+
+    /// Gets a clone of the guild's player, without holding the map's lock.
+    fn get_player(&self, guild_id: GuildId) -> Option<HydrogenPlayer> {
+        self.player.get(&guild_id).map(|player| player.clone())
     }
 
     pub async fn contains_player(&self, guild_id: GuildId) -> bool {
-        self.player.read().await.contains_key(&guild_id)
+        self.player.contains_key(&guild_id)
     }
 
     pub async fn get_voice_channel_id(&self, guild_id: GuildId) -> Option<songbird::id::ChannelId> {
-        let players = self.player.read().await;
-        let connection = players.get(&guild_id)?.connection.read().await;
+        let player = self.get_player(guild_id)?;
+        let connection = player.connection.read().await;
         connection.channel_id
     }
 
     pub async fn skip(&self, guild_id: GuildId) -> Result<Option<HydrogenMusic>> {
-        let players = self.player.read().await;
-
-        let player = players
-            .get(&guild_id)
+        let player = self
+            .get_player(guild_id)
             .ok_or(HydrogenManagerError::PlayerNotFound)?;
 
         player.skip().await.map_err(HydrogenManagerError::Player)
     }
 
     pub async fn prev(&self, guild_id: GuildId) -> Result<Option<HydrogenMusic>> {
-        let players = self.player.read().await;
-
-        let player = players
-            .get(&guild_id)
+        let player = self
+            .get_player(guild_id)
             .ok_or(HydrogenManagerError::PlayerNotFound)?;
 
         player.prev().await.map_err(HydrogenManagerError::Player)
     }
 
+    pub async fn jump(
+        &self,
+        guild_id: GuildId,
+        position: usize,
+        destructive: bool,
+    ) -> Result<Option<HydrogenMusic>> {
+        let player = self
+            .get_player(guild_id)
+            .ok_or(HydrogenManagerError::PlayerNotFound)?;
+
+        player
+            .jump(position, destructive)
+            .await
+            .map_err(HydrogenManagerError::Player)
+    }
+
+    /// Shuffles the queue of the player, returning `false` if the queue is empty.
+    pub async fn shuffle(&self, guild_id: GuildId) -> Result<bool> {
+        let player = self
+            .get_player(guild_id)
+            .ok_or(HydrogenManagerError::PlayerNotFound)?;
+
+        Ok(player.shuffle().await)
+    }
+
+    /// Restores the order of the queue before it was shuffled, returning `false` if it can't be restored.
+    pub async fn unshuffle(&self, guild_id: GuildId) -> Result<bool> {
+        let player = self
+            .get_player(guild_id)
+            .ok_or(HydrogenManagerError::PlayerNotFound)?;
+
+        Ok(player.unshuffle().await.is_some())
+    }
+
     pub async fn seek(
         &self,
         guild_id: GuildId,
         milliseconds: i32,
     ) -> Result<Option<HydrogenSeekCommand>> {
-        let players = self.player.read().await;
-
-        let player = players
-            .get(&guild_id)
+        let player = self
+            .get_player(guild_id)
             .ok_or(HydrogenManagerError::PlayerNotFound)?;
 
         player
@@ -266,12 +577,10 @@ impl HydrogenManager {
         old_voice_state: Option<VoiceState>,
         voice_state: VoiceState,
     ) -> Result<bool> {
-        let players = self.player.read().await;
-
         let guild_id = voice_state
             .guild_id
             .ok_or(HydrogenManagerError::GuildIdMissing)?;
-        let Some(player) = players.get(&guild_id) else {
+        let Some(player) = self.get_player(guild_id) else {
             return Ok(false);
         };
 
@@ -286,8 +595,6 @@ impl HydrogenManager {
                 } else {
                     let is_connected = player.connection.read().await.channel_id.is_some();
                     if is_connected {
-                        drop(players);
-
                         self.destroy(guild_id).await?;
 
                         return Ok(true);
@@ -296,13 +603,52 @@ impl HydrogenManager {
             }
         }
 
-        let connection = player.connection.read().await;
-        if let Some(channel_id) = connection.channel_id {
-            let channel = self
-                .cache
-                .channel(channel_id.0)
-                .ok_or(HydrogenManagerError::GuildChannelNotFound)?
-                .clone();
+        let channel_id = player.connection.read().await.channel_id;
+
+        // Remove the tracks of the users leaving the player's voice channel, if the guild wants it.
+        if voice_state.user_id != self.cache.current_user().id
+            && self.get_remove_on_leave(guild_id)
+            && channel_id.is_some()
+            && old_voice_state
+                .as_ref()
+                .and_then(|v| v.channel_id)
+                .map(songbird::id::ChannelId::from)
+                == channel_id
+            && voice_state.channel_id.map(songbird::id::ChannelId::from) != channel_id
+        {
+            self.remove_requester_tracks(guild_id, &player, voice_state.user_id)
+                .await;
+        }
+
+        if let Some(channel_id) = channel_id {
+            // The channel can be missing from the cache during channel deletions or cache gaps.
+            let channel = match self.cache.channel(channel_id.0).map(|v| v.clone()) {
+                Some(v) => v,
+                None => match self.http.get_channel(channel_id.0.into()).await {
+                    Ok(Channel::Guild(v)) => v,
+                    Ok(_) => return Err(HydrogenManagerError::GuildChannelNotFound),
+                    Err(e) if is_not_found(&e) => {
+                        warn!(
+                            "the voice channel {} in the guild {} doesn't exist anymore, destroying the player",
+                            channel_id.0, guild_id
+                        );
+
+                        self.destroy(guild_id).await?;
+
+                        return Ok(true);
+                    }
+                    Err(e) => return Err(HydrogenManagerError::Serenity(e)),
+                },
+            };
+
+            // The bot joins stage channels as audience, so it needs to become a speaker to be heard.
+            if channel.kind == ChannelType::Stage
+                && voice_state.user_id == self.cache.current_user().id
+                && voice_state.suppress
+                && voice_state.request_to_speak_timestamp.is_none()
+            {
+                self.become_speaker(&channel).await;
+            }
 
             if channel.kind == ChannelType::Voice || channel.kind == ChannelType::Stage {
                 let members_count = channel
@@ -310,26 +656,44 @@ impl HydrogenManager {
                     .map_err(HydrogenManagerError::Serenity)?
                     .len();
 
-                if members_count <= 1 {
+                if members_count <= 1 && self.idle_action == IdleAction::Pause {
+                    // Don't pause again, keeping a player paused by the users paused after they come back.
+                    if !player.pause() {
+                        // Without the auto leave timer, the player resumes when someone comes back.
+                        self.pause_player(guild_id, true, false).await?;
+                        self.idle_paused.insert(guild_id, ());
+                    }
+
+                    self.update_status_message(
+                        guild_id,
+                        &player,
+                        &self
+                            .i18n
+                            .translate(&player.guild_locale(), "player", "idle"),
+                    )
+                    .await;
+                } else if members_count <= 1 {
                     self.timed_destroy(guild_id, Duration::from_secs(HYDROGEN_EMPTY_CHAT_TIMEOUT))
                         .await;
 
-                    self.update_play_message(
+                    self.update_status_message(
                         guild_id,
+                        &player,
                         &self
                             .i18n
                             .translate(&player.guild_locale(), "player", "timeout")
                             .replace("{time}", &HYDROGEN_EMPTY_CHAT_TIMEOUT.to_string()),
-                        HYDROGEN_PRIMARY_COLOR,
-                        HydrogenPlayerState::Thinking,
-                        player.pause(),
-                        player.loop_type().await,
-                        None,
                     )
                     .await;
                 } else {
                     self.cancel_destroy(guild_id).await;
-                    self.update_now_playing(guild_id).await;
+
+                    // Resume only the players paused by being alone, `set_paused` updates the message.
+                    if self.idle_paused.remove(&guild_id).is_some() {
+                        self.set_paused(guild_id, false).await?;
+                    } else {
+                        self.update_now_playing(guild_id).await;
+                    }
                 }
             }
         }
@@ -337,13 +701,96 @@ impl HydrogenManager {
         Ok(true)
     }
 
-    pub async fn update_voice_server(&self, voice_server: VoiceServerUpdateEvent) -> Result<bool> {
-        let players = self.player.read().await;
+    /// Removes the tracks requested by the user who left, telling the channel how many were removed.
+    async fn remove_requester_tracks(
+        &self,
+        guild_id: GuildId,
+        player: &HydrogenPlayer,
+        user_id: UserId,
+    ) {
+        let removed = player.remove_by_requester(user_id).await;
+        if removed == 0 {
+            return;
+        }
+
+        info!(
+            "removed {} tracks requested by the user {} in the guild {}",
+            removed, user_id, guild_id
+        );
 
+        let description = self
+            .i18n
+            .translate(&player.guild_locale(), "player", "requester_left")
+            .replace("{count}", &removed.to_string())
+            .replace("{user}", &format!("<@{}>", user_id));
+
+        if let Err(e) = self.send_notice(player, description).await {
+            warn!("cannot send the removed tracks message: {}", e);
+        }
+    }
+
+    /// Sends a message in the player's text channel, for the events the player message doesn't show.
+    async fn send_notice(
+        &self,
+        player: &HydrogenPlayer,
+        description: String,
+    ) -> result::Result<(), serenity::Error> {
+        player
+            .text_channel_id()
+            .send_message(
+                self.http.clone(),
+                CreateMessage::new().add_embed(
+                    CreateEmbed::new()
+                        .title(
+                            self.i18n
+                                .translate(&player.guild_locale(), "player", "title"),
+                        )
+                        .description(description)
+                        .color(self.primary_color)
+                        .footer(
+                            CreateEmbedFooter::new(self.i18n.translate(
+                                &player.guild_locale(),
+                                "generic",
+                                "embed_footer",
+                            ))
+                            .icon_url(HYDROGEN_LOGO_URL),
+                        ),
+                ),
+            )
+            .await
+            .map(|_| ())
+    }
+
+    /// Becomes a speaker in the stage channel, requesting to speak if the bot can't do it by itself.
+    async fn become_speaker(&self, channel: &GuildChannel) {
+        let Err(e) = channel
+            .edit_own_voice_state(self, EditVoiceState::new().suppress(false))
+            .await
+        else {
+            return;
+        };
+
+        debug!(
+            "cannot become a speaker in the stage channel {}, requesting to speak: {}",
+            channel.id, e
+        );
+
+        if let Err(e) = channel
+            .edit_own_voice_state(self, EditVoiceState::new().request_to_speak(true))
+            .await
+        {
+            warn!(
+                "cannot request to speak in the stage channel {}: {}",
+                channel.id, e
+            );
+        }
+    }
+
+    pub async fn update_voice_server(&self, voice_server: VoiceServerUpdateEvent) -> Result<bool> {
         let guild_id = voice_server
             .guild_id
             .ok_or(HydrogenManagerError::GuildIdMissing)?;
-        let Some(player) = players.get(&guild_id) else {
+        let Some(player) = self.get_player(guild_id) else {
             return Ok(false);
         };
 
@@ -366,21 +813,19 @@ impl HydrogenManager {
     }
 
     pub async fn destroy(&self, guild_id: GuildId) -> Result<()> {
-        let mut players = self.player.write().await;
-        let mut messages = self.message.write().await;
-        let mut destroy_handles = self.destroy_handle.write().await;
-
-        if let Some(player) = players.get(&guild_id) {
+        if let Some(player) = self.get_player(guild_id) {
             player
                 .destroy()
                 .await
                 .map_err(HydrogenManagerError::Player)?;
 
-            if let Some(message) = messages.get(&guild_id) {
+            // Don't hold the lock while deleting, allowing other players to be destroyed at the same time.
+            let message = self.message.read().await.get(&guild_id).copied();
+            if let Some(message) = message {
                 self.http
                     .delete_message(
                         player.text_channel_id(),
-                        *message,
+                        message,
                         Some("Message auto-deleted by timeout."),
                     )
                     .await
@@ -388,22 +833,51 @@ impl HydrogenManager {
             }
         }
 
-        if let Some(destroy_handle) = destroy_handles.get(&guild_id) {
+        self.player.remove(&guild_id);
+        self.idle_paused.remove(&guild_id);
+        self.message.write().await.remove(&guild_id);
+        let destroy_handle = self.destroy_handle.write().await.remove(&guild_id);
+
+        // Abort the tasks without awaiting anything after, as this can be called by one of them.
+        self.cancel_pause_destroy(guild_id);
+        if let Some(destroy_handle) = destroy_handle {
             destroy_handle.abort();
         }
 
-        players.remove(&guild_id);
-        messages.remove(&guild_id);
-        destroy_handles.remove(&guild_id);
-
         Ok(())
     }
 
+    /// Destroys all players concurrently, returning the result of each guild.
+    pub async fn destroy_all(&self) -> Vec<(GuildId, Result<()>)> {
+        let guild_ids = self
+            .player
+            .iter()
+            .map(|player| *player.key())
+            .collect::<Vec<_>>();
+
+        join_all(
+            guild_ids
+                .into_iter()
+                .map(|guild_id| async move { (guild_id, self.destroy(guild_id).await) }),
+        )
+        .await
+    }
+
+    /// Closes the connections to all Lavalink nodes.
+    pub async fn close_lavalink(&self) {
+        let lavalink_nodes = self.lavalink.read().await.clone();
+
+        for (index, node) in lavalink_nodes.iter().enumerate() {
+            if let Err(e) = node.close().await {
+                warn!("cannot close the lavalink node {}: {}", index, e);
+            }
+        }
+    }
+
     pub async fn timed_destroy(&self, guild_id: GuildId, duration: Duration) {
-        let players = self.player.read().await;
         let mut destroy_handles = self.destroy_handle.write().await;
 
-        if players.get(&guild_id).is_some() && destroy_handles.get(&guild_id).is_none() {
+        if self.player.contains_key(&guild_id) && destroy_handles.get(&guild_id).is_none() {
             let self_clone = self.clone();
             let guild_id_clone = guild_id;
             destroy_handles.insert(
@@ -422,6 +896,13 @@ impl HydrogenManager {
         }
     }
 
+    /// Cancels the destruction of a paused player.
+    fn cancel_pause_destroy(&self, guild_id: GuildId) {
+        if let Some((_, handle)) = self.pause_destroy_handle.remove(&guild_id) {
+            handle.abort();
+        }
+    }
+
     pub async fn cancel_destroy(&self, guild_id: GuildId) {
         let mut destroy_handles = self.destroy_handle.write().await;
 
@@ -431,12 +912,52 @@ impl HydrogenManager {
         }
     }
 
+    /// Checks if the player message is shown, because the guild didn't disable it or the users asked for it with
+    /// `/controls`.
+    async fn shows_play_message(&self, guild_id: GuildId) -> bool {
+        self.get_announce(guild_id) || self.message.read().await.contains_key(&guild_id)
+    }
+
+    /// Updates the now playing message, if it's shown.
     async fn update_now_playing(&self, guild_id: GuildId) {
-        if let Some(player) = self.player.read().await.get(&guild_id) {
+        if self.shows_play_message(guild_id).await {
+            self.send_now_playing(guild_id).await;
+        }
+    }
+
+    /// Shows a status in the player message instead of the current track, like while loading, if it's shown.
+    async fn update_status_message(
+        &self,
+        guild_id: GuildId,
+        player: &HydrogenPlayer,
+        description: &str,
+    ) {
+        if !self.shows_play_message(guild_id).await {
+            return;
+        }
+
+        self.update_play_message(
+            guild_id,
+            description,
+            self.primary_color,
+            HydrogenPlayerState::Thinking,
+            player.pause(),
+            player.loop_type().await,
+            None,
+            None,
+        )
+        .await;
+    }
+
+    /// Sends or edits the now playing message, ignoring the guild's announce setting.
+    async fn send_now_playing(&self, guild_id: GuildId) {
+        if let Some(player) = self.get_player(guild_id) {
             let mut player_state = HydrogenPlayerState::Playing;
 
-            let (translated_message, requester) = match player.now().await {
+            let (translated_message, requester, thumbnail) = match player.now().await {
                 Some(v) => {
+                    let thumbnail = artwork_url(&v).to_owned();
+
                     let message = match v.uri {
                         Some(v) => self
                             .i18n
@@ -450,21 +971,22 @@ impl HydrogenManager {
                     .replace("{name}", &v.title)
                     .replace("{author}", &v.author);
 
-                    (message, Some(v.requester_id))
+                    (message, Some(v.requester_id), Some(thumbnail))
                 }
                 None => (
                     self.i18n
                         .translate(&player.guild_locale(), "player", "empty"),
                     None,
+                    None,
                 ),
             };
 
             let mut author_obj = None;
             if let Some(author) = requester {
-                if let Ok(author_user) = author.to_user(self).await {
-                    let mut inner_author_obj = CreateEmbedAuthor::new(author_user.name.clone());
+                if let Some(author_user) = self.get_user(author).await {
+                    let mut inner_author_obj = CreateEmbedAuthor::new(author_user.name);
 
-                    if let Some(avatar_url) = author_user.avatar_url() {
+                    if let Some(avatar_url) = author_user.avatar_url {
                         inner_author_obj = inner_author_obj.icon_url(avatar_url);
                     }
 
@@ -479,16 +1001,30 @@ impl HydrogenManager {
             self.update_play_message(
                 guild_id,
                 &translated_message,
-                HYDROGEN_PRIMARY_COLOR,
+                self.primary_color,
                 player_state,
                 player.pause(),
                 player.loop_type().await,
                 author_obj,
+                thumbnail.as_deref(),
             )
             .await;
         }
     }
 
+    /// Gets the user's name and avatar from the cache, fetching it if needed.
+    async fn get_user(&self, user_id: UserId) -> Option<CachedUser> {
+        self.user_cache
+            .get_or_insert_with(user_id, || async {
+                let user = user_id.to_user(self).await.ok()?;
+                Some(CachedUser {
+                    avatar_url: user.avatar_url(),
+                    name: user.name,
+                })
+            })
+            .await
+    }
+
     // All this type will be refactored in the future.
     #[allow(clippy::too_many_arguments)]
     async fn update_play_message(
@@ -500,42 +1036,43 @@ impl HydrogenManager {
         paused: bool,
         loop_type: LoopType,
         author_obj: Option<CreateEmbedAuthor>,
+        thumbnail: Option<&str>,
     ) {
-        let players = self.player.read().await;
         let mut messages = self.message.write().await;
 
-        if let Some(player) = players.get(&guild_id) {
-            if let Some(message) = messages.get(&guild_id) {
-                let mut embed = CreateEmbed::new();
+        if let Some(player) = self.get_player(guild_id) {
+            let mut embed = CreateEmbed::new()
+                .title(
+                    self.i18n
+                        .translate(&player.guild_locale(), "player", "title"),
+                )
+                .description(description)
+                .color(color)
+                .footer(
+                    CreateEmbedFooter::new(self.i18n.translate(
+                        &player.guild_locale(),
+                        "generic",
+                        "embed_footer",
+                    ))
+                    .icon_url(HYDROGEN_LOGO_URL),
+                );
+
+            if let Some(author_obj) = author_obj {
+                embed = embed.author(author_obj);
+            }
 
-                if let Some(author_obj) = author_obj.clone() {
-                    embed = embed.author(author_obj);
-                }
+            if let Some(thumbnail) = thumbnail {
+                embed = embed.thumbnail(thumbnail);
+            }
 
+            if let Some(message) = messages.get(&guild_id) {
                 match player
                     .text_channel_id()
                     .edit_message(
                         self.http.clone(),
                         message,
                         EditMessage::new()
-                            .embed(
-                                embed
-                                    .title(self.i18n.translate(
-                                        &player.guild_locale(),
-                                        "player",
-                                        "title",
-                                    ))
-                                    .description(description)
-                                    .color(color)
-                                    .footer(
-                                        CreateEmbedFooter::new(self.i18n.translate(
-                                            &player.guild_locale(),
-                                            "generic",
-                                            "embed_footer",
-                                        ))
-                                        .icon_url(HYDROGEN_LOGO_URL),
-                                    ),
-                            )
+                            .embed(embed.clone())
                             .components(Self::play_components(
                                 player_state.clone(),
                                 paused,
@@ -556,24 +1093,7 @@ impl HydrogenManager {
                 .send_message(
                     self.http.clone(),
                     CreateMessage::new()
-                        .add_embed(
-                            CreateEmbed::new()
-                                .title(self.i18n.translate(
-                                    &player.guild_locale(),
-                                    "player",
-                                    "title",
-                                ))
-                                .description(description)
-                                .color(color)
-                                .footer(
-                                    CreateEmbedFooter::new(self.i18n.translate(
-                                        &player.guild_locale(),
-                                        "generic",
-                                        "embed_footer",
-                                    ))
-                                    .icon_url(HYDROGEN_LOGO_URL),
-                                ),
-                        )
+                        .add_embed(embed)
                         .components(Self::play_components(player_state, paused, loop_type)),
                 )
                 .await
@@ -600,8 +1120,7 @@ impl HydrogenManager {
         let mut skip_disabled = false;
         let mut loop_disabled = false;
         let mut stop_disabled = false;
-        // QUEUE WILL REMAIN AS WIP UNTIL ALPHA 4.
-        let mut queue_disabled = true;
+        let mut queue_disabled = false;
 
         let mut pause_emoji = ReactionType::Unicode(String::from("⏸"));
         let mut loop_emoji = ReactionType::Unicode(String::from("⬇️"));
@@ -682,9 +1201,7 @@ impl HydrogenManager {
     }
 
     pub async fn get_loop_type(&self, guild_id: GuildId) -> LoopType {
-        let players = self.player.read().await;
-
-        if let Some(player) = players.get(&guild_id) {
+        if let Some(player) = self.get_player(guild_id) {
             return player.loop_type().await;
         }
 
@@ -692,58 +1209,356 @@ impl HydrogenManager {
     }
 
     pub async fn set_loop_type(&self, guild_id: GuildId, loop_type: LoopType) {
-        let players = self.player.read().await;
-
-        if let Some(player) = players.get(&guild_id) {
+        if let Some(player) = self.get_player(guild_id) {
             player.set_loop_type(loop_type).await;
         }
 
-        drop(players);
         self.update_now_playing(guild_id).await;
     }
 
-    pub async fn get_paused(&self, guild_id: GuildId) -> bool {
-        let players = self.player.read().await;
+    /// Returns the playback state of the guild.
+    pub async fn playback_state(&self, guild_id: GuildId) -> PlaybackState {
+        let Some(player) = self.get_player(guild_id) else {
+            return PlaybackState::NoPlayer;
+        };
 
-        if let Some(player) = players.get(&guild_id) {
-            return player.pause();
+        if player.now().await.is_none() {
+            PlaybackState::Idle
+        } else if player.pause() {
+            PlaybackState::Paused
+        } else {
+            PlaybackState::Playing
         }
-
-        false
     }
 
     pub async fn set_paused(&self, guild_id: GuildId, paused: bool) -> Result<()> {
-        let players = self.player.read().await;
+        self.pause_player(guild_id, paused, true).await
+    }
 
-        if let Some(player) = players.get(&guild_id) {
+    /// Pauses or resumes the player, only starting the auto leave timer if `leave_when_paused` is set.
+    async fn pause_player(
+        &self,
+        guild_id: GuildId,
+        paused: bool,
+        leave_when_paused: bool,
+    ) -> Result<()> {
+        if let Some(player) = self.get_player(guild_id) {
             player
                 .set_pause(paused)
                 .await
                 .map_err(HydrogenManagerError::Player)?;
+
+            self.cancel_pause_destroy(guild_id);
+
+            // Leave if nobody resumes the player in time.
+            if let (true, true, Some(timeout)) = (paused, leave_when_paused, self.auto_leave_paused)
+            {
+                let self_clone = self.clone();
+                self.pause_destroy_handle.insert(
+                    guild_id,
+                    spawn(async move {
+                        sleep(timeout).await;
+
+                        self_clone.pause_destroy_handle.remove(&guild_id);
+                        _ = self_clone.destroy(guild_id).await;
+                    }),
+                );
+            }
         }
 
-        drop(players);
         self.update_now_playing(guild_id).await;
         Ok(())
     }
 
+    /// Returns the current track of the guild and its position in a single snapshot.
+    pub async fn now_with_position(&self, guild_id: GuildId) -> Result<Option<HydrogenNowPlaying>> {
+        self.get_player(guild_id)
+            .ok_or(HydrogenManagerError::PlayerNotFound)?
+            .now_with_position()
+            .await
+            .map_err(HydrogenManagerError::Player)
+    }
+
+    /// Returns the queue of the guild and the index of the current track.
+    pub async fn get_queue(&self, guild_id: GuildId) -> Option<(Vec<HydrogenMusic>, usize)> {
+        let player = self.get_player(guild_id)?;
+
+        Some((player.queue().await, player.index()))
+    }
+
+    /// Sums the lengths of the tracks in the queue, `None` if the player doesn't exist.
+    pub async fn queue_duration(&self, guild_id: GuildId) -> Option<HydrogenQueueDuration> {
+        let player = self.get_player(guild_id)?;
+
+        Some(HydrogenQueueDuration::from(player.queue().await.as_slice()))
+    }
+
+    /// Returns the volume set for the guild, or the default volume.
+    pub fn get_volume(&self, guild_id: GuildId) -> u16 {
+        self.volume
+            .get(&guild_id)
+            .map(|v| *v)
+            .unwrap_or(HYDROGEN_DEFAULT_VOLUME)
+    }
+
+    /// Sets the volume of the guild, applying it to the player if it exists.
+    pub async fn set_volume(&self, guild_id: GuildId, volume: u16) -> Result<()> {
+        let player = self
+            .get_player(guild_id)
+            .ok_or(HydrogenManagerError::PlayerNotFound)?;
+
+        player
+            .set_volume(volume)
+            .await
+            .map_err(HydrogenManagerError::Player)?;
+
+        self.volume.insert(guild_id, volume);
+
+        Ok(())
+    }
+
+    /// Forgets the volume set for the guild, making new players use the default volume.
+    pub fn reset_volume(&self, guild_id: GuildId) {
+        self.volume.remove(&guild_id);
+    }
+
+    /// Returns if the now playing message is enabled in the guild.
+    pub fn get_announce(&self, guild_id: GuildId) -> bool {
+        self.announce.get(&guild_id).map(|v| *v).unwrap_or(true)
+    }
+
+    /// Enables or disables the now playing message in the guild, deleting the current message when disabled.
+    pub async fn set_announce(&self, guild_id: GuildId, announce: bool) {
+        self.announce.insert(guild_id, announce);
+
+        if announce {
+            self.update_now_playing(guild_id).await;
+            return;
+        }
+
+        let mut messages = self.message.write().await;
+        if let Some(player) = self.get_player(guild_id) {
+            if let Some(message) = messages.remove(&guild_id) {
+                if let Err(e) = self
+                    .http
+                    .delete_message(
+                        player.text_channel_id(),
+                        message,
+                        Some("Now playing message disabled."),
+                    )
+                    .await
+                {
+                    warn!("cannot delete the player message: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Returns the source used to search in the guild.
+    pub fn get_search_source(&self, guild_id: GuildId) -> SearchSource {
+        self.search_source
+            .get(&guild_id)
+            .map(|v| *v)
+            .unwrap_or_default()
+    }
+
+    /// Returns if the guild removes the tracks of the users who leave the voice channel.
+    pub fn get_remove_on_leave(&self, guild_id: GuildId) -> bool {
+        self.remove_on_leave
+            .get(&guild_id)
+            .map(|v| *v)
+            .unwrap_or_default()
+    }
+
+    /// Enables or disables removing the tracks of the users who leave the voice channel.
+    pub fn set_remove_on_leave(&self, guild_id: GuildId, enabled: bool) {
+        self.remove_on_leave.insert(guild_id, enabled);
+    }
+
+    /// Returns if the guild asks before enqueueing a track already in the queue.
+    pub fn get_warn_duplicates(&self, guild_id: GuildId) -> bool {
+        self.warn_duplicates
+            .get(&guild_id)
+            .map(|v| *v)
+            .unwrap_or_default()
+    }
+
+    /// Enables or disables asking before enqueueing a track already in the queue.
+    pub fn set_warn_duplicates(&self, guild_id: GuildId, enabled: bool) {
+        self.warn_duplicates.insert(guild_id, enabled);
+    }
+
+    /// Returns the loop type applied to the new players of the guild.
+    pub fn get_default_loop(&self, guild_id: GuildId) -> LoopType {
+        self.default_loop
+            .get(&guild_id)
+            .map(|v| v.clone())
+            .unwrap_or(LoopType::None)
+    }
+
+    /// Changes the loop type applied to the new players of the guild.
+    pub fn set_default_loop(&self, guild_id: GuildId, loop_type: LoopType) {
+        self.default_loop.insert(guild_id, loop_type);
+    }
+
+    /// Changes the source used to search in the guild.
+    pub fn set_search_source(&self, guild_id: GuildId, search_source: SearchSource) {
+        self.search_source.insert(guild_id, search_source);
+    }
+
+    /// Sends the player controls even if the now playing message is disabled, returning the channel where they were sent.
+    ///
+    /// The message is kept updated like the now playing message until the player is destroyed.
+    pub async fn show_controls(&self, guild_id: GuildId) -> Result<ChannelId> {
+        let player = self
+            .get_player(guild_id)
+            .ok_or(HydrogenManagerError::PlayerNotFound)?;
+
+        self.send_now_playing(guild_id).await;
+
+        Ok(player.text_channel_id())
+    }
+
+    /// Moves the player message to another channel, deleting the old message and sending it again in the new channel
+    /// if the now playing message is enabled.
+    pub async fn move_message(&self, guild_id: GuildId, channel_id: ChannelId) -> Result<()> {
+        let player = self
+            .get_player(guild_id)
+            .ok_or(HydrogenManagerError::PlayerNotFound)?;
+
+        let old_channel_id = player.text_channel_id();
+        player.set_text_channel_id(channel_id);
+
+        let message = self.message.write().await.remove(&guild_id);
+        if let Some(message) = message {
+            if let Err(e) = self
+                .http
+                .delete_message(
+                    old_channel_id,
+                    message,
+                    Some("Player message moved to another channel."),
+                )
+                .await
+            {
+                warn!("cannot delete the player message: {}", e);
+            }
+        }
+
+        self.update_now_playing(guild_id).await;
+
+        Ok(())
+    }
+
+    /// Gets a snapshot of all players without requesting Lavalink, so it's O(guilds).
+    ///
+    /// The players are copied from the map in one pass, avoiding holding its shards while reading them.
+    #[cfg(feature = "health")]
+    pub async fn snapshot(&self) -> Vec<HydrogenPlayerSnapshot> {
+        let players = self
+            .player
+            .iter()
+            .map(|player| player.clone())
+            .collect::<Vec<_>>();
+
+        join_all(players.iter().map(|player| player.snapshot())).await
+    }
+
+    /// Gets a snapshot of the connection of every Lavalink node.
+    #[cfg(feature = "health")]
+    pub async fn lavalink_snapshot(&self) -> Vec<LavalinkNodeSnapshot> {
+        let nodes = self.lavalink.read().await.clone();
+
+        join_all(nodes.iter().map(|node| node.snapshot())).await
+    }
+
     /// Returns the number of players.
     pub async fn count_players(&self) -> usize {
-        self.player.read().await.len()
+        self.player.len()
     }
 }
 
 #[async_trait]
 impl LavalinkHandler for HydrogenManager {
-    async fn lavalink_ready(&self, node: Lavalink, _: bool) {
+    async fn lavalink_ready(&self, node: Lavalink, resumed: bool) {
         let timer = Instant::now();
         debug!("(ready): processing...");
 
-        let lavalink_nodes = self.lavalink.read().await;
-        if let Some(index) = find_lavalink(&lavalink_nodes, &node).await {
-            debug!("(ready): lavalink node {} connected", index);
+        {
+            let mut lavalink_nodes = self.lavalink.write().await;
+            if let Some(index) = lavalink_nodes.iter().position(|v| *v == node) {
+                debug!("(ready): lavalink node {} connected", index);
+            } else {
+                // The node was removed when it disconnected, use it again.
+                info!("(ready): lavalink node reconnected");
+                lavalink_nodes.push(node.clone());
+            }
+        }
+
+        // Log the version of the node, helping to find incompatible servers.
+        match node.info().await {
+            Ok(info) => {
+                info!(
+                    "(ready): lavalink node {} is running the version {} with the sources: {}",
+                    node.host(),
+                    info.version.semver,
+                    info.source_managers.join(", ")
+                );
+
+                if info.version.major != LAVALINK_API_VERSION {
+                    warn!(
+                        "(ready): lavalink node {} is running the version {}, but Hydrogen supports the version {}",
+                        node.host(),
+                        info.version.semver,
+                        LAVALINK_API_VERSION
+                    );
+                }
+            }
+            Err(e) => warn!("(ready): cannot get the information of the node: {}", e),
+        }
+
+        // A resumed session keeps its players, the others were lost with the old session.
+        let kept = if resumed {
+            match node.player_guild_ids().await {
+                Ok(guild_ids) => guild_ids,
+                Err(e) => {
+                    warn!("(ready): cannot list the players: {}", e);
+                    Vec::new()
+                }
+            }
         } else {
-            warn!("(ready): unknown lavalink connected");
+            Vec::new()
+        };
+
+        // Destroy the players kept by the session that aren't managed anymore.
+        for guild_id in kept.iter() {
+            if self.player.contains_key(&GuildId::new(*guild_id)) {
+                continue;
+            }
+
+            if let Err(e) = node.destroy_player(*guild_id).await {
+                warn!(
+                    "(ready): cannot destroy the orphan player {}: {}",
+                    guild_id, e
+                );
+            }
+        }
+
+        // Play again the players kept while reconnecting that the node lost.
+        let lost = self
+            .player
+            .iter()
+            .filter(|player| player.lavalink() == node && !kept.contains(&player.key().get()))
+            .map(|player| (*player.key(), player.value().clone()))
+            .collect::<Vec<_>>();
+
+        for (guild_id, player) in lost {
+            if let Err(e) = player.restore().await {
+                warn!("(ready): cannot restore the player {}: {}", guild_id, e);
+
+                if let Err(e) = self.destroy(guild_id).await {
+                    error!("(ready): cannot cleanup player: {}", e);
+                }
+            }
         }
 
         info!("(ready): processed in {}ms", timer.elapsed().as_millis());
@@ -753,25 +1568,40 @@ impl LavalinkHandler for HydrogenManager {
         let timer = Instant::now();
         debug!("(disconnect): processing...");
 
-        let mut lavalink_nodes = self.lavalink.write().await;
-        if let Some(index) = find_lavalink(&lavalink_nodes, &node).await {
-            warn!("(disconnect): lavalink node {} disconnected", index);
-            lavalink_nodes.remove(index);
-        } else {
-            warn!("(disconnect): unknown lavalink disconnected");
-        }
+        let reconnecting = node.connected().await == LavalinkConnection::Connecting;
+
+        {
+            let mut lavalink_nodes = self.lavalink.write().await;
+            if let Some(index) = lavalink_nodes.iter().position(|v| *v == node) {
+                warn!("(disconnect): lavalink node {} disconnected", index);
+                lavalink_nodes.remove(index);
+            } else {
+                warn!("(disconnect): unknown lavalink disconnected");
+            }
 
-        if lavalink_nodes.len() == 0 {
-            error!("(disconnect): no lavalink nodes connected.");
-            exit(1);
+            if lavalink_nodes.len() == 0 {
+                if reconnecting {
+                    warn!(
+                        "(disconnect): no lavalink nodes connected, waiting for the reconnection."
+                    );
+                } else {
+                    error!("(disconnect): no lavalink nodes connected.");
+                    exit(1);
+                }
+            }
         }
 
-        let mut players = self.player.write().await;
-        let players_clone = players.clone();
-        for (guild_id, player) in players_clone.iter() {
-            if node.eq(&player.lavalink()).await {
-                players.remove(guild_id);
-                if let Err(e) = player.destroy().await {
+        // Keep the players while reconnecting, they're restored when the node is ready again.
+        if !reconnecting {
+            let guild_ids = self
+                .player
+                .iter()
+                .filter(|player| player.lavalink() == node)
+                .map(|player| *player.key())
+                .collect::<Vec<_>>();
+
+            for guild_id in guild_ids {
+                if let Err(e) = self.destroy(guild_id).await {
                     error!("(disconnect): cannot cleanup player: {}", e);
                 }
             }
@@ -807,27 +1637,26 @@ impl LavalinkHandler for HydrogenManager {
         let timer = Instant::now();
         debug!("(track_end): processing...");
 
-        match message.reason {
-            LavalinkTrackEndReason::Finished => {
-                let guild_id = match message.guild_id.parse::<u64>() {
-                    Ok(v) => v,
-                    Err(e) => {
-                        warn!("(track_end): invalid GuildId: {}", e);
-                        return;
-                    }
-                };
-                if let Some(player) = self.player.read().await.get(&guild_id.into()) {
-                    if let Err(e) = player.next().await {
-                        warn!("(track_end): cannot go to the next music: {}", e);
-                    }
+        if message.reason == LavalinkTrackEndReason::LoadFailed {
+            error!("(track_end): load failed");
+        }
 
-                    self.update_now_playing(guild_id.into()).await;
+        if TrackEndAction::from(&message.reason) == TrackEndAction::Next {
+            let guild_id = match message.guild_id.parse::<u64>() {
+                Ok(v) => v,
+                Err(e) => {
+                    warn!("(track_end): invalid GuildId: {}", e);
+                    return;
                 }
+            };
+            if let Some(player) = self.get_player(guild_id.into()) {
+                let failed = message.reason == LavalinkTrackEndReason::LoadFailed;
+                if let Err(e) = player.next(failed).await {
+                    warn!("(track_end): cannot go to the next music: {}", e);
+                }
+
+                self.update_now_playing(guild_id.into()).await;
             }
-            LavalinkTrackEndReason::LoadFailed => {
-                error!("(track_end): load failed");
-            }
-            _ => {}
         }
 
         info!(
@@ -869,6 +1698,58 @@ impl LavalinkHandler for HydrogenManager {
             timer.elapsed().as_millis()
         );
     }
+
+    async fn lavalink_websocket_closed(&self, _: Lavalink, message: LavalinkWebSocketClosedEvent) {
+        let timer = Instant::now();
+        debug!("(websocket_closed): processing...");
+
+        warn!(
+            "(websocket_closed): voice connection closed in the guild {} with code {}: {}",
+            message.guild_id, message.code, message.reason
+        );
+
+        let guild_id = match message.guild_id.parse::<u64>() {
+            Ok(v) => GuildId::new(v),
+            Err(e) => {
+                warn!("(websocket_closed): invalid GuildId: {}", e);
+                return;
+            }
+        };
+
+        if let Some(player) = self.get_player(guild_id) {
+            let mut destroy = VoiceClosedAction::from(&message) == VoiceClosedAction::Destroy;
+
+            if VoiceClosedAction::from(&message) == VoiceClosedAction::Reconnect {
+                match player.reconnect().await {
+                    Ok(_) => debug!("(websocket_closed): reconnected in the guild {}", guild_id),
+                    Err(e) => {
+                        error!("(websocket_closed): cannot reconnect: {}", e);
+                        destroy = true;
+                    }
+                }
+            }
+
+            if destroy {
+                if let Err(e) = self.destroy(guild_id).await {
+                    error!("(websocket_closed): cannot destroy the player: {}", e);
+                }
+
+                // The player message is removed by destroy, so the reason is sent in a new message.
+                let description =
+                    self.i18n
+                        .translate(&player.guild_locale(), "player", "voice_closed");
+
+                if let Err(e) = self.send_notice(&player, description).await {
+                    warn!("(websocket_closed): cannot send the message: {}", e);
+                }
+            }
+        }
+
+        info!(
+            "(websocket_closed): processed in {}ms",
+            timer.elapsed().as_millis()
+        );
+    }
 }
 
 impl CacheHttp for HydrogenManager {
@@ -880,13 +1761,284 @@ impl CacheHttp for HydrogenManager {
     }
 }
 
-async fn find_lavalink(nodes: &[Lavalink], lavalink: &Lavalink) -> Option<usize> {
-    for i in 0..nodes.len() {
-        if let Some(node) = nodes.get(i) {
-            if node.eq(lavalink).await {
-                return Some(i);
+/// Checks if the Discord API responded that the resource doesn't exist.
+fn is_not_found(error: &serenity::Error) -> bool {
+    matches!(error, serenity::Error::Http(e) if e.status_code().map(|v| v.as_u16()) == Some(404))
+}
+
+/// Runs the load until it finishes, checking if the requester is still present at every interval and after the load.
+///
+/// Returns `None` if the requester isn't present anymore.
+async fn load_while_present<T>(
+    load: impl Future<Output = T>,
+    present: impl Fn() -> bool,
+    interval: Duration,
+) -> Option<T> {
+    let left = async {
+        loop {
+            sleep(interval).await;
+
+            if !present() {
+                break;
             }
         }
+    };
+
+    let result = select! {
+        result = load => result,
+        _ = left => return None,
+    };
+
+    present().then_some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use async_tungstenite::tungstenite::Message;
+    use futures::SinkExt;
+    use tokio::net::TcpListener;
+
+    use super::*;
+    use crate::player::HydrogenPlayerConnection;
+
+    /// Handler ignoring every event of the fake node.
+    struct IgnoredHandler;
+
+    #[async_trait]
+    impl LavalinkHandler for IgnoredHandler {}
+
+    /// Connects to a fake node that only sends the ready message, failing every REST request.
+    async fn fake_lavalink() -> Lavalink {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        spawn(async move {
+            let mut sockets = Vec::new();
+            while let Ok((stream, _)) = listener.accept().await {
+                if let Ok(mut socket) = async_tungstenite::tokio::accept_async(stream).await {
+                    _ = socket
+                        .send(Message::Text(
+                            r#"{"op":"ready","resumed":false,"sessionId":"abc"}"#.to_owned(),
+                        ))
+                        .await;
+                    sockets.push(socket);
+                }
+            }
+        });
+
+        Lavalink::connect(
+            LavalinkNodeInfo {
+                host: address.to_string(),
+                password: "youshallnotpass".to_owned(),
+                tls: false,
+                ping_interval: 0,
+                path_prefix: None,
+                reconnect_attempts: 0,
+                resume_timeout: 0,
+            },
+            1,
+            lavalink::http_client(),
+            IgnoredHandler,
+        )
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn idle_paused_player_doesnt_leave() {
+        let manager = HydrogenManager::new(
+            Arc::new(Cache::new()),
+            Arc::new(Http::new("")),
+            Arc::new(I18n::new()),
+            0,
+            Arc::new(TrackCache::new(0, Duration::ZERO)),
+            Some(Duration::from_millis(50)),
+            IdleAction::Pause,
+            None,
+            NodeCapacity::default(),
+        );
+        let guild_id = GuildId::new(1);
+        manager.announce.insert(guild_id, false);
+        manager.player.insert(
+            guild_id,
+            HydrogenPlayer::new(
+                fake_lavalink().await,
+                guild_id,
+                Songbird::serenity(),
+                HydrogenPlayerConnection::new("", "", "", None),
+                ChannelId::new(1),
+                "en-US",
+                HYDROGEN_DEFAULT_VOLUME,
+                Arc::new(TrackCache::new(0, Duration::ZERO)),
+            ),
+        );
+
+        manager.pause_player(guild_id, true, false).await.unwrap();
+        sleep(Duration::from_millis(150)).await;
+
+        assert!(manager.pause_destroy_handle.is_empty());
+        assert!(manager.get_player(guild_id).is_some_and(|v| v.pause()));
+    }
+
+    fn manager() -> HydrogenManager {
+        HydrogenManager::new(
+            Arc::new(Cache::new()),
+            Arc::new(Http::new("")),
+            Arc::new(I18n::new()),
+            0,
+            Arc::new(TrackCache::new(0, Duration::ZERO)),
+            None,
+            IdleAction::default(),
+            None,
+            NodeCapacity::default(),
+        )
+    }
+
+    #[test]
+    fn node_capacity_limits() {
+        let stats: LavalinkStats = serde_json::from_value(serde_json::json!({
+            "players": 8,
+            "playingPlayers": 5,
+            "cpu": { "cores": 4, "systemLoad": 0.5, "lavalinkLoad": 0.9 }
+        }))
+        .unwrap();
+
+        // Unlimited by default, even without the stats.
+        assert!(!NodeCapacity::default().is_full(1000, Some(&stats)));
+        assert!(!NodeCapacity::default().is_full(0, None));
+
+        let players = NodeCapacity {
+            max_players: Some(8),
+            max_cpu_load: None,
+        };
+        assert!(!players.is_full(2, None));
+        assert!(players.is_full(8, None));
+        // The stats count the players created by other clients too.
+        assert!(players.is_full(2, Some(&stats)));
+
+        let cpu = NodeCapacity {
+            max_players: None,
+            max_cpu_load: Some(90),
+        };
+        assert!(cpu.is_full(0, Some(&stats)));
+        assert!(!cpu.is_full(0, None));
+    }
+
+    #[test]
+    fn clones_share_the_guild_settings() {
+        let manager = manager();
+        let clone = manager.clone();
+        let guild_id = GuildId::new(1);
+
+        clone.set_search_source(guild_id, SearchSource::SoundCloud);
+        clone.set_remove_on_leave(guild_id, true);
+
+        assert!(manager.get_search_source(guild_id) == SearchSource::SoundCloud);
+        assert!(manager.get_remove_on_leave(guild_id));
+        assert!(Arc::ptr_eq(&manager.player, &clone.player));
+        assert!(Arc::ptr_eq(&manager.lavalink, &clone.lavalink));
+    }
+
+    #[test]
+    fn idle_action_from_name() {
+        assert_eq!(IdleAction::from_name("Pause"), Some(IdleAction::Pause));
+        assert_eq!(IdleAction::from_name("leave"), Some(IdleAction::Leave));
+        assert_eq!(IdleAction::from_name("stay"), None);
+    }
+
+    fn websocket_closed(code: u16, by_remote: bool) -> LavalinkWebSocketClosedEvent {
+        LavalinkWebSocketClosedEvent {
+            guild_id: "1".to_owned(),
+            code,
+            reason: String::new(),
+            by_remote,
+        }
+    }
+
+    #[test]
+    fn invalid_session_reconnects() {
+        assert_eq!(
+            VoiceClosedAction::from(&websocket_closed(4006, true)),
+            VoiceClosedAction::Reconnect
+        );
+    }
+
+    #[test]
+    fn unknown_close_code_destroys() {
+        assert_eq!(
+            VoiceClosedAction::from(&websocket_closed(4004, true)),
+            VoiceClosedAction::Destroy
+        );
+        assert_eq!(
+            VoiceClosedAction::from(&websocket_closed(4004, false)),
+            VoiceClosedAction::Ignore
+        );
+    }
+
+    #[test]
+    fn finished_goes_to_next() {
+        assert_eq!(
+            TrackEndAction::from(&LavalinkTrackEndReason::Finished),
+            TrackEndAction::Next
+        );
+    }
+
+    #[test]
+    fn load_failed_goes_to_next() {
+        assert_eq!(
+            TrackEndAction::from(&LavalinkTrackEndReason::LoadFailed),
+            TrackEndAction::Next
+        );
+    }
+
+    #[test]
+    fn stopped_keeps_queue() {
+        assert_eq!(
+            TrackEndAction::from(&LavalinkTrackEndReason::Stopped),
+            TrackEndAction::Keep
+        );
+    }
+
+    #[test]
+    fn replaced_keeps_queue() {
+        assert_eq!(
+            TrackEndAction::from(&LavalinkTrackEndReason::Replaced),
+            TrackEndAction::Keep
+        );
+    }
+
+    #[test]
+    fn cleanup_keeps_queue() {
+        assert_eq!(
+            TrackEndAction::from(&LavalinkTrackEndReason::Cleanup),
+            TrackEndAction::Keep
+        );
+    }
+
+    #[tokio::test]
+    async fn load_finishes_when_requester_stays() {
+        let result = load_while_present(async { 1 }, || true, Duration::from_millis(5)).await;
+
+        assert_eq!(result, Some(1));
+    }
+
+    #[tokio::test]
+    async fn load_aborts_when_requester_leaves() {
+        let present = AtomicBool::new(true);
+        let load = async {
+            present.store(false, Ordering::Relaxed);
+            sleep(Duration::from_millis(100)).await;
+            1
+        };
+
+        let result = load_while_present(
+            load,
+            || present.load(Ordering::Relaxed),
+            Duration::from_millis(5),
+        )
+        .await;
+
+        assert_eq!(result, None);
     }
-    None
 }
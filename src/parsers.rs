@@ -74,6 +74,28 @@ impl TimeParser {
 
         Some(hours_minutes + (seconds * 1000))
     }
+
+    /// Formats a number of seconds into a syntax accepted by the parsers.
+    ///
+    /// Uses the suffix syntax when the time fits in a single unit (like `90m`), falling back to the semicolon syntax.
+    pub fn format(seconds: u64) -> String {
+        if seconds % 3600 == 0 && seconds / 3600 <= 999 && seconds != 0 {
+            format!("{}h", seconds / 3600)
+        } else if seconds % 60 == 0 && seconds / 60 <= 999 && seconds != 0 {
+            format!("{}m", seconds / 60)
+        } else if seconds <= 999 {
+            format!("{}s", seconds)
+        } else if seconds < 3600 {
+            format!("{}:{:02}", seconds / 60, seconds % 60)
+        } else {
+            format!(
+                "{}:{:02}:{:02}",
+                seconds / 3600,
+                seconds % 3600 / 60,
+                seconds % 60
+            )
+        }
+    }
 }
 
 /// Holds the parser for the roll syntax.
@@ -168,3 +190,37 @@ impl RollParser {
         Some(params)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::TimeParser;
+
+    /// Parses a time using any of the supported syntaxes, returning the number of seconds.
+    fn parse(parser: &TimeParser, data: &str) -> Option<u64> {
+        parser
+            .suffix_syntax(data)
+            .or_else(|| parser.semicolon_syntax(data))
+            .map(|v| u64::from(v) / 1000)
+    }
+
+    #[test]
+    fn format_uses_the_largest_unit() {
+        assert_eq!(TimeParser::format(0), "0s");
+        assert_eq!(TimeParser::format(45), "45s");
+        assert_eq!(TimeParser::format(120), "2m");
+        assert_eq!(TimeParser::format(5400), "90m");
+        assert_eq!(TimeParser::format(7200), "2h");
+        assert_eq!(TimeParser::format(1000), "16:40");
+        assert_eq!(TimeParser::format(5401), "1:30:01");
+    }
+
+    #[test]
+    fn format_round_trips() {
+        let parser = TimeParser::new().unwrap();
+
+        for seconds in [0, 1, 59, 60, 61, 999, 1000, 3599, 3600, 5400, 5401, 86399] {
+            let formatted = TimeParser::format(seconds);
+            assert_eq!(parse(&parser, &formatted), Some(seconds), "{}", formatted);
+        }
+    }
+}
@@ -1,61 +1,247 @@
 use std::{
-    fmt::Display,
+    fmt::{Debug, Display},
+    ops::Range,
     result,
+    str::FromStr,
     sync::{
-        atomic::{AtomicBool, AtomicUsize, Ordering},
+        atomic::{AtomicBool, AtomicU16, AtomicU64, AtomicUsize, Ordering},
         Arc,
     },
+    time::Duration,
 };
 
-use rand::Rng;
+use rand::{seq::SliceRandom, Rng};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 use serenity::model::prelude::{ChannelId, GuildId, UserId};
 use songbird::{error::JoinError, ConnectionInfo, Songbird};
 use tokio::sync::RwLock;
 
 use crate::{
+    cache::TrackCache,
     lavalink::{
-        rest::{LavalinkLoadResultType, LavalinkTrack, LavalinkUpdatePlayer, LavalinkVoiceState},
-        Lavalink, LavalinkConnection, LavalinkError,
+        rest::{
+            LavalinkException, LavalinkLoadResultType, LavalinkTrack, LavalinkTrackInfo,
+            LavalinkTrackLoading, LavalinkUpdatePlayer, LavalinkVoiceState,
+        },
+        Lavalink, LavalinkConnection, LavalinkError, REDACTED,
     },
-    HYDROGEN_QUEUE_LIMIT, HYDROGEN_SEARCH_PREFIX,
+    HYDROGEN_DUPLICATE_SCAN_LIMIT, HYDROGEN_LOAD_FAILURE_LIMIT, HYDROGEN_QUEUE_LIMIT,
 };
 
-#[derive(Clone, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum LoopType {
+    /// Plays the queue once, stopping after the last track.
     None,
+    /// Stops after every track, waiting for the users to skip, jump, seek or resume to play again.
     NoAutostart,
     Music,
     Queue,
     Random,
 }
 
-#[derive(Clone)]
+impl LoopType {
+    /// Every loop type, in the order they are switched by the player button.
+    pub const ALL: [Self; 5] = [
+        Self::None,
+        Self::NoAutostart,
+        Self::Music,
+        Self::Queue,
+        Self::Random,
+    ];
+
+    /// Gets the name used in the commands, the same one used by serde.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::NoAutostart => "no_autostart",
+            Self::Music => "music",
+            Self::Queue => "queue",
+            Self::Random => "random",
+        }
+    }
+
+    /// Gets the key of the loop type's name in the `loop` translations.
+    pub fn translation_key(&self) -> &'static str {
+        match self {
+            Self::None => "autostart",
+            other => other.name(),
+        }
+    }
+}
+
+impl Display for LoopType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The name doesn't match any loop type.
+#[derive(Debug)]
+pub struct UnknownLoopType(pub String);
+
+impl Display for UnknownLoopType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "unknown loop type '{}'", self.0)
+    }
+}
+
+impl FromStr for LoopType {
+    type Err = UnknownLoopType;
+
+    fn from_str(s: &str) -> result::Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase();
+
+        Self::ALL
+            .into_iter()
+            .find(|v| v.name() == name)
+            .ok_or(UnknownLoopType(s.to_owned()))
+    }
+}
+
+/// Source used to search the queries that aren't URLs.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchSource {
+    #[default]
+    YouTube,
+    YouTubeMusic,
+    SoundCloud,
+}
+
+impl SearchSource {
+    /// Gets the source from the name used in the commands.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "youtube" => Some(Self::YouTube),
+            "youtube_music" => Some(Self::YouTubeMusic),
+            "soundcloud" => Some(Self::SoundCloud),
+            _ => None,
+        }
+    }
+
+    /// Gets the name used in the commands.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::YouTube => "youtube",
+            Self::YouTubeMusic => "youtube_music",
+            Self::SoundCloud => "soundcloud",
+        }
+    }
+
+    /// Gets the Lavalink prefix used to search in this source.
+    pub fn prefix(&self) -> &'static str {
+        match self {
+            Self::YouTube => "ytsearch:",
+            Self::YouTubeMusic => "ytmsearch:",
+            Self::SoundCloud => "scsearch:",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct HydrogenMusic {
     pub encoded_track: String,
+    /// Identifier of the track in its source, falling back to the encoded track.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub identifier: String,
     pub length: i32,
     pub author: String,
     pub title: String,
     pub uri: Option<String>,
+    /// Name of the source where the track was loaded from, like `youtube` or `soundcloud`.
+    pub source: Option<String>,
+    /// Artwork of the track, provided by its source.
+    pub artwork: Option<String>,
     pub requester_id: UserId,
 }
 
 impl HydrogenMusic {
     pub fn from(value: LavalinkTrack, requester_id: UserId) -> Self {
+        let artwork = track_artwork(&value.info);
+        let identifier = track_identifier(&value);
+
         HydrogenMusic {
+            identifier,
             encoded_track: value.encoded,
             length: value.info.length,
             author: value.info.author,
             title: value.info.title,
             uri: value.info.uri,
+            source: Some(value.info.source_name),
+            artwork,
             requester_id,
         }
     }
+
+    /// Gets the identifier of the track, falling back to the encoded track for the tracks stored without it.
+    fn identity(&self) -> &str {
+        if self.identifier.is_empty() {
+            &self.encoded_track
+        } else {
+            &self.identifier
+        }
+    }
+
+    /// Checks if the track was loaded from the same source, comparing the sources and identifiers.
+    pub fn same_source(&self, track: &LavalinkTrack) -> bool {
+        self.source.as_deref() == Some(track.info.source_name.as_str())
+            && self.identity() == track_identifier(track)
+    }
+}
+
+/// Gets the identifier of the track in its source, falling back to the encoded track when it's empty.
+fn track_identifier(track: &LavalinkTrack) -> String {
+    if track.info.identifier.is_empty() {
+        track.encoded.clone()
+    } else {
+        track.info.identifier.clone()
+    }
+}
+
+/// Checks if the track is in the last `limit` tracks of the queue.
+fn is_duplicate(queue: &[HydrogenMusic], track: &LavalinkTrack, limit: usize) -> bool {
+    queue
+        .iter()
+        .rev()
+        .take(limit)
+        .any(|music| music.same_source(track))
+}
+
+/// Clamps the seek time to the start of the track, returning `None` if it's beyond the track's length.
+fn seek_position(milliseconds: i32, length: i32) -> Option<i32> {
+    if milliseconds > length {
+        return None;
+    }
+
+    Some(milliseconds.max(0))
+}
+
+/// Gets the artwork of the track, using the YouTube thumbnail when the node doesn't provide one.
+///
+/// Empty and non-HTTP URLs are discarded, as Discord rejects them in the embeds.
+fn track_artwork(info: &LavalinkTrackInfo) -> Option<String> {
+    let artwork = match info.artwork_url.as_deref() {
+        Some(url) if !url.trim().is_empty() => Some(url.trim().to_owned()),
+        _ if info.source_name == "youtube" && !info.identifier.is_empty() => Some(format!(
+            "https://img.youtube.com/vi/{}/hqdefault.jpg",
+            info.identifier
+        )),
+        _ => None,
+    };
+
+    artwork.filter(|url| url.starts_with("https://") || url.starts_with("http://"))
 }
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum HydrogenPlayerError {
     Lavalink(LavalinkError),
     Join(JoinError),
+    /// The seek time is beyond the length of the current track, which is given in milliseconds.
+    SeekBeyondLength(i32),
 }
 
 impl Display for HydrogenPlayerError {
@@ -63,6 +249,9 @@ impl Display for HydrogenPlayerError {
         match self {
             Self::Lavalink(e) => e.fmt(f),
             Self::Join(e) => e.fmt(f),
+            Self::SeekBeyondLength(length) => {
+                write!(f, "seek time beyond the track length of {}ms", length)
+            }
         }
     }
 }
@@ -77,6 +266,17 @@ pub struct HydrogenPlayerConnection {
     pub channel_id: Option<songbird::id::ChannelId>,
 }
 
+impl Debug for HydrogenPlayerConnection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HydrogenPlayerConnection")
+            .field("session_id", &self.session_id)
+            .field("token", &REDACTED)
+            .field("endpoint", &self.endpoint)
+            .field("channel_id", &self.channel_id)
+            .finish()
+    }
+}
+
 impl HydrogenPlayerConnection {
     pub fn new(
         session_id: &str,
@@ -110,19 +310,128 @@ impl From<ConnectionInfo> for HydrogenPlayerConnection {
     }
 }
 
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct HydrogenPlayCommand {
     pub track: Option<HydrogenMusic>,
     pub count: usize,
     pub playing: bool,
     pub truncated: bool,
+    /// Name of the loaded playlist, if the source provides it.
+    pub playlist_name: Option<String>,
+    /// Why Lavalink failed to load the query.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub exception: Option<LavalinkException>,
+    /// If the tracks were truncated by the requester's limit instead of the queue's.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub user_limited: bool,
 }
 
+#[derive(Debug)]
 pub struct HydrogenSeekCommand {
     pub position: i32,
     pub total: i32,
     pub track: HydrogenMusic,
 }
 
+/// Snapshot of the current track and its position.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct HydrogenNowPlaying {
+    /// Position of the track in milliseconds.
+    pub position: i32,
+    /// Length of the track in milliseconds.
+    pub total: i32,
+    /// Index of the track in the queue.
+    pub index: usize,
+    pub track: HydrogenMusic,
+}
+
+/// Progress of a track, shared by the results that carry its position and length.
+pub trait TrackProgress {
+    /// Position of the track in milliseconds.
+    fn position(&self) -> i32;
+
+    /// Length of the track in milliseconds, `0` for streams.
+    fn total(&self) -> i32;
+
+    /// How much of the track was played, from `0.0` to `1.0`.
+    fn progress_ratio(&self) -> f32 {
+        if self.total() <= 0 {
+            return 0.0;
+        }
+
+        (self.position() as f32 / self.total() as f32).clamp(0.0, 1.0)
+    }
+
+    /// How much of the track is left to play.
+    fn remaining(&self) -> Duration {
+        let remaining = self.total().saturating_sub(self.position()).max(0);
+        Duration::from_millis(remaining as u64)
+    }
+}
+
+impl TrackProgress for HydrogenSeekCommand {
+    fn position(&self) -> i32 {
+        self.position
+    }
+
+    fn total(&self) -> i32 {
+        self.total
+    }
+}
+
+impl TrackProgress for HydrogenNowPlaying {
+    fn position(&self) -> i32 {
+        self.position
+    }
+
+    fn total(&self) -> i32 {
+        self.total
+    }
+}
+
+/// Total length of the tracks in a queue.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HydrogenQueueDuration {
+    /// Sum of the lengths of the tracks, excluding the streams.
+    pub total: Duration,
+    /// Amount of streams, which have no length.
+    pub streams: usize,
+}
+
+impl From<&[HydrogenMusic]> for HydrogenQueueDuration {
+    fn from(queue: &[HydrogenMusic]) -> Self {
+        queue.iter().fold(Self::default(), |mut duration, track| {
+            if track.length > 0 {
+                duration.total += Duration::from_millis(track.length as u64);
+            } else {
+                duration.streams += 1;
+            }
+
+            duration
+        })
+    }
+}
+
+/// Read-only snapshot of a player's state, used by external tooling like dashboards.
+#[cfg(feature = "health")]
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HydrogenPlayerSnapshot {
+    pub guild_id: GuildId,
+    /// The current track, `None` if the queue is empty.
+    pub track: Option<HydrogenMusic>,
+    /// Last position of the current track sent by Lavalink in milliseconds, `None` if it isn't playing it.
+    pub position: Option<i32>,
+    pub queue_length: usize,
+    pub loop_type: LoopType,
+    pub paused: bool,
+    pub volume: u16,
+}
+
+/// Player of a guild, playing its queue in a Lavalink node.
+///
+/// Cloning is cheap and the clones share the queue, the connection and the playback state.
 #[derive(Clone)]
 pub struct HydrogenPlayer {
     pub connection: Arc<RwLock<HydrogenPlayerConnection>>,
@@ -133,9 +442,18 @@ pub struct HydrogenPlayer {
     lavalink: Lavalink,
     queue: Arc<RwLock<Vec<HydrogenMusic>>>,
     queue_loop: Arc<RwLock<LoopType>>,
-    text_channel_id: ChannelId,
+    /// Original position of each track while the queue is shuffled.
+    original_order: Arc<RwLock<Option<Vec<usize>>>>,
+    /// Channel where the player message is sent, changed by `/move`.
+    text_channel_id: Arc<AtomicU64>,
     voice_manager: Arc<Songbird>,
     paused: Arc<AtomicBool>,
+    /// Tracks that failed to load in a row, stopping the queue when it reaches the limit.
+    load_failures: Arc<AtomicUsize>,
+    /// If the player was paused by the loop type after a track ended, not by the users.
+    auto_paused: Arc<AtomicBool>,
+    volume: Arc<AtomicU16>,
+    track_cache: Arc<TrackCache>,
 }
 
 impl HydrogenPlayer {
@@ -146,19 +464,26 @@ impl HydrogenPlayer {
         connection: HydrogenPlayerConnection,
         text_channel_id: ChannelId,
         guild_locale: &str,
+        volume: u16,
+        track_cache: Arc<TrackCache>,
     ) -> Self {
         Self {
             connection: Arc::new(RwLock::new(connection)),
             destroyed: Arc::new(AtomicBool::new(false)),
             index: Arc::new(AtomicUsize::new(0)),
             paused: Arc::new(AtomicBool::new(false)),
+            load_failures: Arc::new(AtomicUsize::new(0)),
+            auto_paused: Arc::new(AtomicBool::new(false)),
             queue: Arc::new(RwLock::new(Vec::new())),
             queue_loop: Arc::new(RwLock::new(LoopType::None)),
+            original_order: Arc::new(RwLock::new(None)),
+            volume: Arc::new(AtomicU16::new(volume)),
             guild_locale: guild_locale.to_owned(),
             guild_id,
             lavalink,
-            text_channel_id,
+            text_channel_id: Arc::new(AtomicU64::new(text_channel_id.get())),
             voice_manager,
+            track_cache,
         }
     }
 
@@ -200,12 +525,18 @@ impl HydrogenPlayer {
 
         if has_player {
             self.lavalink
-                .update_player(self.guild_id.get(), true, &player)
+                .update_player(
+                    self.guild_id.get(),
+                    PlayerUpdate::Pause.no_replace(),
+                    &player,
+                )
                 .await
                 .map_err(HydrogenPlayerError::Lavalink)?;
         }
 
         self.paused.store(paused, Ordering::Relaxed);
+        self.load_failures.store(0, Ordering::Relaxed);
+        self.auto_paused.store(false, Ordering::Relaxed);
 
         if !has_player && !paused {
             self.start_playing().await?;
@@ -214,12 +545,35 @@ impl HydrogenPlayer {
         Ok(())
     }
 
+    pub fn volume(&self) -> u16 {
+        self.volume.load(Ordering::Relaxed)
+    }
+
+    pub async fn set_volume(&self, volume: u16) -> Result<()> {
+        if self.lavalink.get_player(self.guild_id.get()).await.is_ok() {
+            self.lavalink
+                .set_volume(self.guild_id.get(), volume)
+                .await
+                .map_err(HydrogenPlayerError::Lavalink)?;
+        }
+
+        self.volume.store(volume, Ordering::Relaxed);
+
+        Ok(())
+    }
+
     pub fn lavalink(&self) -> Lavalink {
         self.lavalink.clone()
     }
 
     pub fn text_channel_id(&self) -> ChannelId {
+        ChannelId::new(self.text_channel_id.load(Ordering::Relaxed))
+    }
+
+    /// Changes the channel where the player message is sent.
+    pub fn set_text_channel_id(&self, channel_id: ChannelId) {
         self.text_channel_id
+            .store(channel_id.get(), Ordering::Relaxed);
     }
 
     pub fn guild_locale(&self) -> String {
@@ -238,7 +592,78 @@ impl HydrogenPlayer {
         self.queue.read().await.clone()
     }
 
+    /// Returns the current track and its position from a single Lavalink request.
+    pub async fn now_with_position(&self) -> Result<Option<HydrogenNowPlaying>> {
+        let player = self
+            .lavalink
+            .get_player(self.guild_id.get())
+            .await
+            .map_err(HydrogenPlayerError::Lavalink)?;
+
+        let Some(track) = player.track else {
+            return Ok(None);
+        };
+
+        // Find the track being played by Lavalink, as the index can change between the requests.
+        let queue = self.queue.read().await;
+        let index = self.index.load(Ordering::Relaxed);
+        let index = if queue
+            .get(index)
+            .is_some_and(|music| music.encoded_track == track.encoded)
+        {
+            index
+        } else {
+            match queue
+                .iter()
+                .position(|music| music.encoded_track == track.encoded)
+            {
+                Some(v) => v,
+                None => return Ok(None),
+            }
+        };
+
+        Ok(Some(HydrogenNowPlaying {
+            position: track.info.position,
+            total: track.info.length,
+            index,
+            track: queue[index].clone(),
+        }))
+    }
+
+    /// Returns the index of the current track in the queue.
+    pub fn index(&self) -> usize {
+        self.index.load(Ordering::Relaxed)
+    }
+
+    /// Gets a snapshot of the player's state without requesting Lavalink.
+    ///
+    /// The loop type and the queue are locked together, like when moving to the next track, so the snapshot doesn't
+    /// mix the states before and after a change.
+    #[cfg(feature = "health")]
+    pub async fn snapshot(&self) -> HydrogenPlayerSnapshot {
+        let queue_loop = self.queue_loop.read().await;
+        let queue = self.queue.read().await;
+
+        let track = queue.get(self.index.load(Ordering::Relaxed)).cloned();
+        let position = track
+            .as_ref()
+            .and(self.lavalink.last_player_update(self.guild_id.get()))
+            .map(|state| state.position);
+
+        HydrogenPlayerSnapshot {
+            guild_id: self.guild_id,
+            track,
+            position,
+            queue_length: queue.len(),
+            loop_type: queue_loop.clone(),
+            paused: self.pause(),
+            volume: self.volume(),
+        }
+    }
+
     pub async fn skip(&self) -> Result<Option<HydrogenMusic>> {
+        self.resume_auto_paused();
+
         let queue = self.queue.read().await;
         let mut index = self.index.fetch_add(1, Ordering::Relaxed) + 1;
         if index >= queue.len() {
@@ -250,6 +675,8 @@ impl HydrogenPlayer {
     }
 
     pub async fn prev(&self) -> Result<Option<HydrogenMusic>> {
+        self.resume_auto_paused();
+
         let queue = self.queue.read().await;
         let mut index = self.index.load(Ordering::Relaxed);
         if index == 0 {
@@ -262,68 +689,207 @@ impl HydrogenPlayer {
         Ok(queue.get(index).cloned())
     }
 
-    pub async fn next(&self) -> Result<()> {
+    /// Jumps to the track in the position, removing the tracks between the current and the target if `destructive` and
+    /// the target is after the current track.
+    pub async fn jump(&self, position: usize, destructive: bool) -> Result<Option<HydrogenMusic>> {
+        let mut queue = self.queue.write().await;
+        if position >= queue.len() {
+            return Ok(None);
+        }
+
+        let mut index = position;
+        if destructive {
+            let (removed, new_index) = jump_range(self.index.load(Ordering::Relaxed), position);
+            index = new_index;
+
+            // The original order can't be restored without the removed tracks.
+            if !removed.is_empty() {
+                queue.drain(removed);
+                *self.original_order.write().await = None;
+            }
+        }
+
+        self.index.store(index, Ordering::Relaxed);
+        let music = queue.get(index).cloned();
+        drop(queue);
+
+        self.resume_auto_paused();
+        self.start_playing().await?;
+        Ok(music)
+    }
+
+    /// Shuffles the queue, moving the current track to the start, returning `false` if the queue is empty.
+    ///
+    /// The order before the first shuffle is kept, allowing it to be restored by [`Self::unshuffle`].
+    pub async fn shuffle(&self) -> bool {
+        let mut queue = self.queue.write().await;
+        let current = self.index.load(Ordering::Relaxed);
+        if current >= queue.len() {
+            return false;
+        }
+
+        let mut original_order = self.original_order.write().await;
+        let order = original_order
+            .take()
+            .unwrap_or_else(|| (0..queue.len()).collect());
+
+        *original_order = Some(shuffle_queue(
+            &mut queue,
+            order,
+            current,
+            &mut rand::thread_rng(),
+        ));
+        self.index.store(0, Ordering::Relaxed);
+
+        true
+    }
+
+    /// Restores the order of the queue before it was shuffled, returning the new index of the current track.
+    ///
+    /// Returns `None` if the queue isn't shuffled or was changed after the shuffle.
+    pub async fn unshuffle(&self) -> Option<usize> {
+        let mut queue = self.queue.write().await;
+        let order = self.original_order.write().await.take()?;
+
+        let index = restore_queue(&mut queue, order, self.index.load(Ordering::Relaxed))?;
+        self.index.store(index, Ordering::Relaxed);
+
+        Some(index)
+    }
+
+    /// Removes the tracks requested by the user, keeping the current one, and returns how many were removed.
+    pub async fn remove_by_requester(&self, requester_id: UserId) -> usize {
+        let mut queue = self.queue.write().await;
+        let (removed, index) =
+            remove_tracks(&mut queue, self.index.load(Ordering::Relaxed), |music| {
+                music.requester_id == requester_id
+            });
+
+        if removed > 0 {
+            self.index.store(index, Ordering::Relaxed);
+
+            // The original order can't be restored without the removed tracks.
+            *self.original_order.write().await = None;
+        }
+
+        removed
+    }
+
+    /// Moves to the next track when the current one ends, following the loop type.
+    ///
+    /// If the track `failed` to load, it isn't played again by the music loop, and the queue stops after too many
+    /// tracks failed in a row.
+    pub async fn next(&self, failed: bool) -> Result<()> {
         let queue_loop = self.queue_loop.read().await;
         let queue = self.queue.read().await;
 
-        if queue_loop.ne(&LoopType::NoAutostart) {
-            if queue_loop.ne(&LoopType::Music) {
-                if queue_loop.ne(&LoopType::Random) {
-                    let index = self.index.fetch_add(1, Ordering::Relaxed) + 1;
-                    if index >= queue.len() {
-                        if queue_loop.eq(&LoopType::Queue) {
-                            self.index.store(0, Ordering::Relaxed);
-                            self.start_playing().await?;
-                        } else {
-                            self.index.store(queue.len() - 1, Ordering::Relaxed);
-                            self.paused.store(true, Ordering::Relaxed);
-                        }
-                    } else {
-                        self.start_playing().await?;
-                    }
-                } else {
-                    let random_index = rand::thread_rng().gen_range(0..queue.len());
-                    self.index.store(random_index, Ordering::Relaxed);
-                    self.start_playing().await?;
-                }
-            } else {
-                self.start_playing().await?;
-            }
+        let failures = if failed {
+            self.load_failures.fetch_add(1, Ordering::Relaxed) + 1
+        } else {
+            self.load_failures.store(0, Ordering::Relaxed);
+            0
+        };
+
+        let (index, play) = next_index(
+            &queue_loop,
+            self.index.load(Ordering::Relaxed),
+            queue.len(),
+            failed,
+            &mut rand::thread_rng(),
+        );
+        self.index.store(index, Ordering::Relaxed);
+
+        if play && !too_many_failures(failures, queue.len()) {
+            self.start_playing().await?;
         } else {
-            let index = self.index.fetch_add(1, Ordering::Relaxed) + 1;
-            if index >= queue.len() {
-                self.index.store(queue.len() - 1, Ordering::Relaxed);
-            }
             self.paused.store(true, Ordering::Relaxed);
+            self.auto_paused.store(true, Ordering::Relaxed);
         }
+
         Ok(())
     }
 
-    pub async fn play(&self, music: &str, requester_id: UserId) -> Result<HydrogenPlayCommand> {
-        let musics = {
-            let mut musics = self
-                .lavalink
-                .track_load(music)
-                .await
-                .map_err(HydrogenPlayerError::Lavalink)?;
+    /// Resumes the player if it was stopped by the loop type, as the users asked to play something.
+    ///
+    /// Returns `true` if it was stopped, meaning Lavalink isn't playing any track.
+    fn resume_auto_paused(&self) -> bool {
+        self.load_failures.store(0, Ordering::Relaxed);
+        resume_auto_paused(&self.paused, &self.auto_paused)
+    }
 
-            if musics.tracks.is_empty() {
-                musics = self
-                    .lavalink
-                    .track_load(&format!("{}{}", HYDROGEN_SEARCH_PREFIX, music))
-                    .await
-                    .map_err(HydrogenPlayerError::Lavalink)?;
-            }
+    /// Loads the query, searching it in the source if it isn't a URL.
+    pub async fn load(
+        &self,
+        music: &str,
+        search_source: SearchSource,
+    ) -> Result<LavalinkTrackLoading> {
+        let mut musics = self.track_load(music).await?;
+
+        // Search for the query when it isn't a URL, as there's no reason to search a URL that failed to load.
+        if musics.load_type != LavalinkLoadResultType::LoadFailed && musics.tracks.is_empty() {
+            musics = self
+                .track_load(&format!("{}{}", search_source.prefix(), music))
+                .await?;
+        }
+
+        Ok(musics)
+    }
+
+    /// Checks if the loaded track is already in the queue, playlists are never considered duplicates.
+    pub async fn contains_duplicate(&self, musics: &LavalinkTrackLoading) -> bool {
+        if musics.load_type == LavalinkLoadResultType::PlaylistLoaded {
+            return false;
+        }
 
-            musics
+        let Some(track) = musics.tracks.first() else {
+            return false;
         };
 
+        is_duplicate(
+            &self.queue.read().await,
+            track,
+            HYDROGEN_DUPLICATE_SCAN_LIMIT,
+        )
+    }
+
+    /// Enqueues the loaded tracks, starting to play them if the player is stopped.
+    ///
+    /// `user_limit` is the maximum of tracks in the queue requested by the same user, `None` disables it.
+    pub async fn play(
+        &self,
+        musics: LavalinkTrackLoading,
+        requester_id: UserId,
+        user_limit: Option<usize>,
+    ) -> Result<HydrogenPlayCommand> {
+        if musics.load_type == LavalinkLoadResultType::LoadFailed {
+            return Ok(HydrogenPlayCommand {
+                track: None,
+                count: 0,
+                playing: false,
+                truncated: false,
+                playlist_name: None,
+                exception: musics.exception,
+                user_limited: false,
+            });
+        }
+
         let mut truncated = false;
         let starting_index = self.queue.read().await.len();
+
+        let (limit, user_limited) = {
+            let queue = self.queue.read().await;
+            let requested = queue
+                .iter()
+                .filter(|music| music.requester_id == requester_id)
+                .count();
+
+            queue_limit(queue.len(), requested, user_limit)
+        };
+
         if musics.load_type == LavalinkLoadResultType::SearchResult {
             if let Some(music) = musics.tracks.first() {
                 let queue_length = self.queue.read().await.len();
-                if queue_length < HYDROGEN_QUEUE_LIMIT {
+                if queue_length < limit {
                     self.queue
                         .write()
                         .await
@@ -337,23 +903,25 @@ impl HydrogenPlayer {
                     count: 0,
                     playing: false,
                     truncated: false,
+                    playlist_name: None,
+                    exception: None,
+                    user_limited: false,
                 });
             }
         } else {
-            for music in musics.tracks.iter() {
-                let queue_length = self.queue.read().await.len();
-                if queue_length < HYDROGEN_QUEUE_LIMIT {
-                    self.queue
-                        .write()
-                        .await
-                        .push(HydrogenMusic::from(music.clone(), requester_id));
-                } else {
-                    truncated = true;
-                    break;
-                }
-            }
+            truncated = enqueue(
+                &mut *self.queue.write().await,
+                musics
+                    .tracks
+                    .iter()
+                    .map(|music| HydrogenMusic::from(music.clone(), requester_id)),
+                limit,
+            );
         }
 
+        // The new tracks don't have an original position to be restored to.
+        *self.original_order.write().await = None;
+
         let mut playing = false;
 
         let lavalink_not_playing = match self.lavalink.get_player(self.guild_id.get()).await {
@@ -388,6 +956,8 @@ impl HydrogenPlayer {
 
             self.index.store(index, Ordering::Relaxed);
             self.paused.store(false, Ordering::Relaxed);
+            self.load_failures.store(0, Ordering::Relaxed);
+            self.auto_paused.store(false, Ordering::Relaxed);
 
             playing = self.start_playing().await?;
             if playing {
@@ -400,30 +970,77 @@ impl HydrogenPlayer {
             count: self.queue.read().await.len() - starting_index,
             playing,
             truncated,
+            playlist_name: match musics.load_type {
+                LavalinkLoadResultType::PlaylistLoaded => musics.playlist_info.name,
+                _ => None,
+            },
+            exception: None,
+            user_limited: truncated && user_limited,
         })
     }
 
+    async fn track_load(&self, identifier: &str) -> Result<LavalinkTrackLoading> {
+        if let Some(musics) = self.track_cache.get(identifier) {
+            return Ok(musics);
+        }
+
+        let musics = self
+            .lavalink
+            .track_load(identifier)
+            .await
+            .map_err(HydrogenPlayerError::Lavalink)?;
+
+        self.track_cache.insert(identifier, &musics);
+
+        Ok(musics)
+    }
+
     pub async fn seek(&self, milliseconds: i32) -> Result<Option<HydrogenSeekCommand>> {
+        let Some(music) = self.now().await else {
+            return Ok(None);
+        };
+
+        let position = seek_position(milliseconds, music.length)
+            .ok_or(HydrogenPlayerError::SeekBeyondLength(music.length))?;
+
+        // The track already ended, so it's played again from the position.
+        if self.resume_auto_paused() {
+            self.play_current(Some(position)).await?;
+
+            return Ok(Some(HydrogenSeekCommand {
+                position,
+                total: music.length,
+                track: music,
+            }));
+        }
+
         let mut update_player = LavalinkUpdatePlayer::new();
-        update_player.position(milliseconds);
+        update_player.position(position);
         let player = self
             .lavalink
-            .update_player(self.guild_id.get(), false, &update_player)
+            .update_player(
+                self.guild_id.get(),
+                PlayerUpdate::Seek.no_replace(),
+                &update_player,
+            )
             .await
             .map_err(HydrogenPlayerError::Lavalink)?;
         if let Some(track) = player.track {
-            if let Some(music) = self.now().await {
-                return Ok(Some(HydrogenSeekCommand {
-                    position: track.info.position,
-                    total: track.info.length,
-                    track: music,
-                }));
-            }
+            return Ok(Some(HydrogenSeekCommand {
+                position: track.info.position,
+                total: track.info.length,
+                track: music,
+            }));
         }
         Ok(None)
     }
 
     async fn start_playing(&self) -> Result<bool> {
+        self.play_current(None).await
+    }
+
+    /// Plays the current track from the position, or from the start if it's `None`.
+    async fn play_current(&self, position: Option<i32>) -> Result<bool> {
         let connection = self.connection.read().await;
         if let Some(music) = self
             .queue
@@ -435,10 +1052,19 @@ impl HydrogenPlayer {
             player
                 .encoded_track(&music.encoded_track)
                 .voice_state(connection.clone().into())
-                .paused(self.paused.load(Ordering::Relaxed));
+                .paused(self.paused.load(Ordering::Relaxed))
+                .volume(self.volume());
+
+            if let Some(position) = position {
+                player.position(position);
+            }
 
             self.lavalink
-                .update_player(self.guild_id.get(), false, &player)
+                .update_player(
+                    self.guild_id.get(),
+                    PlayerUpdate::Play.no_replace(),
+                    &player,
+                )
                 .await
                 .map_err(HydrogenPlayerError::Lavalink)?;
 
@@ -467,16 +1093,757 @@ impl HydrogenPlayer {
         Ok(())
     }
 
+    /// Sends the voice connection to Lavalink, restarting the current track at the same position if it was playing, as
+    /// the audio can cut out after a voice region change.
     pub async fn update_connection(&self) -> Result<()> {
-        let connection = self.connection.read().await;
-        let mut player = LavalinkUpdatePlayer::new();
-        player.voice_state(connection.clone().into());
-
-        self.lavalink
-            .update_player(self.guild_id.get(), true, &player)
+        let playing = self
+            .lavalink
+            .get_player(self.guild_id.get())
             .await
-            .map_err(HydrogenPlayerError::Lavalink)?;
+            .ok()
+            .and_then(|player| player.track);
+
+        let connection = self.connection.read().await.clone();
+
+        match playing {
+            Some(track) => {
+                let player = connection_update(
+                    connection.into(),
+                    Some((&track.encoded, track.info.position)),
+                );
+
+                self.lavalink
+                    .update_player(
+                        self.guild_id.get(),
+                        PlayerUpdate::Restart.no_replace(),
+                        &player,
+                    )
+                    .await
+                    .map_err(HydrogenPlayerError::Lavalink)?;
+            }
+            None => {
+                self.lavalink
+                    .update_voice(self.guild_id.get(), connection.into())
+                    .await
+                    .map_err(HydrogenPlayerError::Lavalink)?;
+            }
+        }
 
         Ok(())
     }
+
+    /// Plays the current track again after the node lost the player, continuing from the last position it sent.
+    ///
+    /// The node stops the audio when it disconnects, so the position isn't advanced. Returns `false` if there's nothing
+    /// to play.
+    pub async fn restore(&self) -> Result<bool> {
+        // The queue already ended, keep waiting for the users.
+        if self.auto_paused.load(Ordering::Relaxed) {
+            return Ok(false);
+        }
+
+        let position = self
+            .lavalink
+            .last_player_update(self.guild_id.get())
+            .map(|state| state.position);
+
+        self.play_current(position).await
+    }
+
+    /// Joins the voice channel again, creating a new voice session and sending it to Lavalink.
+    ///
+    /// Returns `false` if the player isn't connected to a voice channel.
+    pub async fn reconnect(&self) -> Result<bool> {
+        let Some(channel_id) = self.connection.read().await.channel_id else {
+            return Ok(false);
+        };
+
+        let (connection_info, _) = self
+            .voice_manager
+            .join_gateway(self.guild_id, channel_id)
+            .await
+            .map_err(HydrogenPlayerError::Join)?;
+
+        *self.connection.write().await = connection_info.into();
+
+        self.update_connection().await?;
+
+        Ok(true)
+    }
+}
+
+/// Operations that update the Lavalink player, the volume is changed by `Lavalink::set_volume` instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PlayerUpdate {
+    /// Starts playing the current track of the queue.
+    Play,
+    /// Restarts the current track at its position after the voice connection changed.
+    Restart,
+    /// Pauses or resumes the player, also starting the current track if nothing is playing.
+    Pause,
+    /// Changes the position of the current track.
+    Seek,
+}
+
+impl PlayerUpdate {
+    /// Returns the `noReplace` sent with the update.
+    ///
+    /// Only the operations meant to start a track can replace the current one, the others can't restart it.
+    fn no_replace(self) -> bool {
+        match self {
+            Self::Play | Self::Restart => false,
+            Self::Pause | Self::Seek => true,
+        }
+    }
+}
+
+/// Calculates the tracks removed by a destructive jump and the index of the target after the removal.
+///
+/// Only the tracks skipped by jumping forward are removed, jumping backward keeps the tracks already played.
+fn jump_range(current: usize, target: usize) -> (Range<usize>, usize) {
+    if target > current {
+        (current + 1..target, current + 1)
+    } else {
+        (target..target, target)
+    }
+}
+
+/// Checks if the queue stops after the tracks that failed to load in a row, as every track in it can be failing.
+fn too_many_failures(failures: usize, length: usize) -> bool {
+    failures > 0 && failures >= HYDROGEN_LOAD_FAILURE_LIMIT.min(length)
+}
+
+/// Builds the update sent when the voice connection changes, replaying the track at its position if it was playing.
+fn connection_update(
+    voice_state: LavalinkVoiceState,
+    playing: Option<(&str, i32)>,
+) -> LavalinkUpdatePlayer {
+    let mut player = LavalinkUpdatePlayer::new();
+    player.voice_state(voice_state);
+
+    if let Some((encoded_track, position)) = playing {
+        player.encoded_track(encoded_track).position(position);
+    }
+
+    player
+}
+
+/// Removes the tracks matching the predicate, except the current one.
+///
+/// Returns how many tracks were removed and the new index of the current track.
+fn remove_tracks<T>(
+    queue: &mut Vec<T>,
+    current: usize,
+    predicate: impl Fn(&T) -> bool,
+) -> (usize, usize) {
+    let length = queue.len();
+    let removed_before = queue.iter().take(current).filter(|v| predicate(v)).count();
+
+    let mut position = 0;
+    queue.retain(|track| {
+        let keep = position == current || !predicate(track);
+        position += 1;
+        keep
+    });
+
+    (length - queue.len(), current - removed_before)
+}
+
+/// Gets the limit of the queue for the requester, returning if it's limited by the requester's tracks instead of the queue.
+///
+/// `requested` is the amount of tracks in the queue already requested by the requester.
+fn queue_limit(queue_length: usize, requested: usize, user_limit: Option<usize>) -> (usize, bool) {
+    let Some(user_limit) = user_limit else {
+        return (HYDROGEN_QUEUE_LIMIT, false);
+    };
+
+    let limit = queue_length + user_limit.saturating_sub(requested);
+    if limit < HYDROGEN_QUEUE_LIMIT {
+        (limit, true)
+    } else {
+        (HYDROGEN_QUEUE_LIMIT, false)
+    }
+}
+
+/// Adds the tracks to the queue without exceeding the limit, reserving the space needed at once.
+///
+/// Returns `true` if some tracks were ignored because of the limit.
+fn enqueue<T>(queue: &mut Vec<T>, tracks: impl ExactSizeIterator<Item = T>, limit: usize) -> bool {
+    let available = limit.saturating_sub(queue.len());
+    let truncated = tracks.len() > available;
+
+    queue.reserve(tracks.len().min(available));
+    queue.extend(tracks.take(available));
+
+    truncated
+}
+
+/// Clears the pause set by the loop type, keeping the pause set by the users, returning if it was set.
+fn resume_auto_paused(paused: &AtomicBool, auto_paused: &AtomicBool) -> bool {
+    if !auto_paused.swap(false, Ordering::Relaxed) {
+        return false;
+    }
+
+    paused.store(false, Ordering::Relaxed);
+    true
+}
+
+/// Gets the index of the track played after the current one ends and if it should be played, pausing the player otherwise.
+///
+/// A track that `failed` to load isn't played again by the music loop, which moves to the next track like the queue
+/// loop. The queue can't be empty.
+fn next_index(
+    loop_type: &LoopType,
+    current: usize,
+    length: usize,
+    failed: bool,
+    rng: &mut impl Rng,
+) -> (usize, bool) {
+    let next = current + 1;
+
+    match loop_type {
+        LoopType::Music if !failed => (current, true),
+        LoopType::Random => (rng.gen_range(0..length), true),
+        LoopType::NoAutostart => (next.min(length - 1), false),
+        LoopType::None | LoopType::Queue | LoopType::Music if next < length => (next, true),
+        LoopType::Queue | LoopType::Music => (0, true),
+        LoopType::None => (length - 1, false),
+    }
+}
+
+/// Shuffles the queue, moving the current track to the start, and returns the shuffled order.
+///
+/// `order` is the original position of each track, carried to the shuffled queue.
+fn shuffle_queue<T>(
+    queue: &mut Vec<T>,
+    order: Vec<usize>,
+    current: usize,
+    rng: &mut impl Rng,
+) -> Vec<usize> {
+    let mut tracks = order.into_iter().zip(queue.drain(..)).collect::<Vec<_>>();
+    tracks.swap(0, current);
+
+    if let Some(upcoming) = tracks.get_mut(1..) {
+        upcoming.shuffle(rng);
+    }
+
+    let (order, tracks) = tracks.into_iter().unzip();
+    *queue = tracks;
+    order
+}
+
+/// Moves the tracks back to their original position, returning the new index of the current track.
+///
+/// Returns `None` without changing the queue if the order doesn't match the queue.
+fn restore_queue<T>(queue: &mut Vec<T>, order: Vec<usize>, current: usize) -> Option<usize> {
+    if order.len() != queue.len() {
+        return None;
+    }
+
+    let index = *order.get(current)?;
+
+    let mut tracks = order.into_iter().zip(queue.drain(..)).collect::<Vec<_>>();
+    tracks.sort_unstable_by_key(|(position, _)| *position);
+    queue.extend(tracks.into_iter().map(|(_, track)| track));
+
+    Some(index)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicBool, Ordering},
+        time::Duration,
+    };
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::{
+        connection_update, enqueue, is_duplicate, jump_range, next_index, queue_limit,
+        remove_tracks, restore_queue, resume_auto_paused, seek_position, shuffle_queue,
+        too_many_failures, track_artwork, HydrogenMusic, HydrogenQueueDuration, LavalinkTrack,
+        LavalinkTrackInfo, LavalinkVoiceState, LoopType, PlayerUpdate, TrackProgress, UserId,
+        HYDROGEN_LOAD_FAILURE_LIMIT, HYDROGEN_QUEUE_LIMIT,
+    };
+
+    /// Simulates a destructive jump in a queue, returning the queue and the target.
+    fn jump(queue: &[u8], current: usize, target: usize) -> (Vec<u8>, u8) {
+        let mut queue = queue.to_vec();
+        let (removed, index) = jump_range(current, target);
+        queue.drain(removed);
+        let track = queue[index];
+        (queue, track)
+    }
+
+    #[test]
+    fn only_play_and_restart_replace_the_track() {
+        assert!(!PlayerUpdate::Play.no_replace());
+        assert!(!PlayerUpdate::Restart.no_replace());
+        assert!(PlayerUpdate::Pause.no_replace());
+        assert!(PlayerUpdate::Seek.no_replace());
+    }
+
+    /// Progress of a track without the rest of the player's results.
+    struct Progress(i32, i32);
+
+    impl TrackProgress for Progress {
+        fn position(&self) -> i32 {
+            self.0
+        }
+
+        fn total(&self) -> i32 {
+            self.1
+        }
+    }
+
+    #[test]
+    fn progress_of_a_track() {
+        assert_eq!(Progress(50000, 200000).progress_ratio(), 0.25);
+        assert_eq!(Progress(250000, 200000).progress_ratio(), 1.0);
+        assert_eq!(
+            Progress(50000, 200000).remaining(),
+            Duration::from_millis(150000)
+        );
+    }
+
+    #[test]
+    fn progress_of_a_stream() {
+        assert_eq!(Progress(50000, 0).progress_ratio(), 0.0);
+        assert_eq!(Progress(50000, 0).remaining(), Duration::ZERO);
+    }
+
+    #[test]
+    fn seek_position_is_clamped_to_the_track() {
+        assert_eq!(seek_position(-5000, 212000), Some(0));
+        assert_eq!(seek_position(42000, 212000), Some(42000));
+        assert_eq!(seek_position(212000, 212000), Some(212000));
+        assert_eq!(seek_position(212001, 212000), None);
+    }
+
+    #[test]
+    fn loop_type_round_trip() {
+        for loop_type in LoopType::ALL {
+            assert_eq!(
+                loop_type.to_string().parse::<LoopType>().ok(),
+                Some(loop_type)
+            );
+        }
+    }
+
+    #[test]
+    fn loop_type_translation_keys() {
+        assert_eq!(LoopType::None.translation_key(), "autostart");
+        assert_eq!(LoopType::NoAutostart.translation_key(), "no_autostart");
+    }
+
+    #[test]
+    fn loop_type_parse_is_case_insensitive() {
+        assert_eq!(
+            " No_Autostart ".parse::<LoopType>().ok(),
+            Some(LoopType::NoAutostart)
+        );
+        assert!("forever".parse::<LoopType>().is_err());
+    }
+
+    fn track_info(source_name: &str, artwork_url: Option<&str>) -> LavalinkTrackInfo {
+        LavalinkTrackInfo {
+            identifier: "dQw4w9WgXcQ".to_owned(),
+            is_seekable: true,
+            author: "Rick Astley".to_owned(),
+            length: 212000,
+            is_stream: false,
+            position: 0,
+            title: "Never Gonna Give You Up".to_owned(),
+            uri: None,
+            source_name: source_name.to_owned(),
+            artwork_url: artwork_url.map(|v| v.to_owned()),
+        }
+    }
+
+    #[test]
+    fn artwork_prefers_the_node_url() {
+        let info = track_info("youtube", Some("https://example.com/cover.png"));
+        assert_eq!(
+            track_artwork(&info).as_deref(),
+            Some("https://example.com/cover.png")
+        );
+    }
+
+    #[test]
+    fn artwork_falls_back_to_the_youtube_thumbnail() {
+        let info = track_info("youtube", Some("  "));
+        assert_eq!(
+            track_artwork(&info).as_deref(),
+            Some("https://img.youtube.com/vi/dQw4w9WgXcQ/hqdefault.jpg")
+        );
+    }
+
+    #[test]
+    fn artwork_discards_invalid_urls() {
+        assert!(track_artwork(&track_info("soundcloud", None)).is_none());
+        assert!(track_artwork(&track_info("http", Some("attachment://cover.png"))).is_none());
+    }
+
+    #[test]
+    fn jump_forward_removes_skipped_tracks() {
+        assert_eq!(jump(&[1, 2, 3, 4, 5], 0, 4), (vec![1, 5], 5));
+        assert_eq!(jump(&[1, 2, 3, 4, 5], 1, 2), (vec![1, 2, 3, 4, 5], 3));
+    }
+
+    #[test]
+    fn jump_backward_keeps_played_tracks() {
+        assert_eq!(jump(&[1, 2, 3, 4, 5], 4, 0), (vec![1, 2, 3, 4, 5], 1));
+        assert_eq!(jump(&[1, 2, 3, 4, 5], 3, 2), (vec![1, 2, 3, 4, 5], 3));
+    }
+
+    #[test]
+    fn jump_to_current_keeps_queue() {
+        assert_eq!(jump(&[1, 2, 3], 1, 1), (vec![1, 2, 3], 2));
+    }
+
+    #[test]
+    fn shuffle_moves_the_current_track_to_the_start() {
+        let mut queue = (0..50).collect::<Vec<_>>();
+        let order = shuffle_queue(
+            &mut queue,
+            (0..50).collect(),
+            7,
+            &mut StdRng::seed_from_u64(1),
+        );
+
+        assert_eq!(queue[0], 7);
+        assert_eq!(queue, order);
+        assert_ne!(queue, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unshuffle_restores_the_original_order() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut queue = (0..50).collect::<Vec<_>>();
+
+        // Shuffle twice, restoring the order before the first shuffle.
+        let order = shuffle_queue(&mut queue, (0..50).collect(), 7, &mut rng);
+        let order = shuffle_queue(&mut queue, order, 3, &mut rng);
+        let current = queue[0];
+
+        assert_eq!(restore_queue(&mut queue, order, 0), Some(current));
+        assert_eq!(queue, (0..50).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn unshuffle_rejects_a_changed_queue() {
+        let mut queue = vec![1, 2, 3];
+
+        assert_eq!(restore_queue(&mut queue, vec![1, 0], 0), None);
+        assert_eq!(queue, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn remove_tracks_keeps_the_current_track() {
+        let mut queue = vec![1, 2, 1, 3, 1, 4, 1];
+
+        // Two tracks before and one after the current track are removed.
+        assert_eq!(remove_tracks(&mut queue, 4, |v| *v == 1), (3, 2));
+        assert_eq!(queue, vec![2, 3, 1, 4]);
+        assert_eq!(queue[2], 1);
+    }
+
+    #[test]
+    fn remove_tracks_without_matches() {
+        let mut queue = vec![1, 2, 3];
+
+        assert_eq!(remove_tracks(&mut queue, 1, |v| *v == 4), (0, 1));
+        assert_eq!(queue, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn queue_limit_without_user_limit() {
+        assert_eq!(queue_limit(10, 10, None), (HYDROGEN_QUEUE_LIMIT, false));
+    }
+
+    #[test]
+    fn queue_limit_counts_the_requester_tracks() {
+        // 5 tracks requested by the user and 15 by others, leaving 5 for the user.
+        assert_eq!(queue_limit(20, 5, Some(10)), (25, true));
+        assert_eq!(queue_limit(20, 15, Some(10)), (20, true));
+        assert_eq!(
+            queue_limit(HYDROGEN_QUEUE_LIMIT - 1, 0, Some(10)),
+            (HYDROGEN_QUEUE_LIMIT, false)
+        );
+    }
+
+    fn track(encoded: &str, uri: Option<&str>) -> LavalinkTrack {
+        let mut info = track_info("youtube", None);
+        info.uri = uri.map(|v| v.to_owned());
+        info.identifier = uri
+            .and_then(|v| v.rsplit('/').next())
+            .unwrap_or(encoded)
+            .to_owned();
+
+        LavalinkTrack {
+            encoded: encoded.to_owned(),
+            track: encoded.to_owned(),
+            info,
+        }
+    }
+
+    #[test]
+    fn queue_duration_skips_the_streams() {
+        let music = |length| HydrogenMusic {
+            length,
+            ..HydrogenMusic::from(track("QAAA", None), UserId::new(1))
+        };
+        let queue = [music(60000), music(0), music(83 * 60000), music(0)];
+
+        assert_eq!(
+            HydrogenQueueDuration::from(&queue[..]),
+            HydrogenQueueDuration {
+                total: Duration::from_secs(84 * 60),
+                streams: 2,
+            }
+        );
+        assert_eq!(
+            HydrogenQueueDuration::from(&[][..]),
+            HydrogenQueueDuration::default()
+        );
+    }
+
+    #[test]
+    fn duplicate_is_found_by_source_and_identifier() {
+        let queue = vec![
+            HydrogenMusic::from(track("QAAA", Some("https://youtu.be/a")), UserId::new(1)),
+            HydrogenMusic::from(track("QAAB", None), UserId::new(1)),
+        ];
+
+        assert!(is_duplicate(
+            &queue,
+            &track("QAAC", Some("https://youtu.be/a")),
+            10
+        ));
+        assert!(is_duplicate(&queue, &track("QAAB", None), 10));
+        assert!(!is_duplicate(
+            &queue,
+            &track("QAAD", Some("https://youtu.be/b")),
+            10
+        ));
+    }
+
+    #[test]
+    fn tracks_are_identified_by_source_and_identifier() {
+        let track = |source: &str, identifier: &str| {
+            let mut track = track("QAAA", None);
+            track.info.source_name = source.to_owned();
+            track.info.identifier = identifier.to_owned();
+            track
+        };
+        let music = HydrogenMusic::from(track("youtube", "a"), UserId::new(1));
+
+        assert!(music.same_source(&track("youtube", "a")));
+        assert!(!music.same_source(&track("youtube", "b")));
+        assert!(!music.same_source(&track("soundcloud", "a")));
+
+        // Without an identifier, the encoded track is used instead.
+        let unidentified = HydrogenMusic::from(track("http", ""), UserId::new(1));
+        assert_eq!(unidentified.identifier, "QAAA");
+        assert!(unidentified.same_source(&track("http", "")));
+    }
+
+    #[test]
+    fn duplicate_scan_is_bounded() {
+        let queue = vec![
+            HydrogenMusic::from(track("QAAA", None), UserId::new(1)),
+            HydrogenMusic::from(track("QAAB", None), UserId::new(1)),
+        ];
+
+        assert!(!is_duplicate(&queue, &track("QAAA", None), 1));
+        assert!(is_duplicate(&queue, &track("QAAB", None), 1));
+    }
+
+    #[test]
+    fn enqueue_reserves_the_tracks() {
+        let mut queue = Vec::new();
+
+        assert!(!enqueue(&mut queue, 0..100, 1000));
+        assert_eq!(queue.len(), 100);
+        assert!(queue.capacity() >= 100);
+    }
+
+    #[test]
+    fn enqueue_respects_the_limit() {
+        let mut queue = vec![0; 5];
+
+        assert!(enqueue(&mut queue, 0..100, 10));
+        assert_eq!(queue, [0, 0, 0, 0, 0, 0, 1, 2, 3, 4]);
+
+        // A full queue ignores every track.
+        assert!(enqueue(&mut queue, 0..1, 10));
+        assert_eq!(queue.len(), 10);
+
+        let mut queue = Vec::new();
+        assert!(!enqueue(&mut queue, 0..10, 10));
+        assert_eq!(queue.len(), 10);
+    }
+
+    #[test]
+    fn region_change_while_playing_restarts_the_track() {
+        let voice_state = LavalinkVoiceState::new("token", "brazil.discord.media", "session");
+        let update =
+            serde_json::to_value(connection_update(voice_state, Some(("QAAA", 42000)))).unwrap();
+
+        assert_eq!(update["encodedTrack"], "QAAA");
+        assert_eq!(update["position"], 42000);
+        assert_eq!(update["voice"]["endpoint"], "brazil.discord.media");
+    }
+
+    #[test]
+    fn region_change_while_stopped_only_updates_the_voice() {
+        let voice_state = LavalinkVoiceState::new("token", "brazil.discord.media", "session");
+        let update = serde_json::to_value(connection_update(voice_state, None)).unwrap();
+
+        assert!(update.get("encodedTrack").is_none());
+        assert!(update.get("position").is_none());
+        assert_eq!(update["voice"]["endpoint"], "brazil.discord.media");
+    }
+
+    /// Expected behavior of each loop type in a queue with 3 tracks, as `(current, next, played)`:
+    ///
+    /// | Loop type      | From 0       | From 1       | From 2        |
+    /// |----------------|--------------|--------------|---------------|
+    /// | `None`         | 1, played    | 2, played    | 2, paused     |
+    /// | `NoAutostart`  | 1, paused    | 2, paused    | 2, paused     |
+    /// | `Music`        | 0, played    | 1, played    | 2, played     |
+    /// | `Queue`        | 1, played    | 2, played    | 0, played     |
+    const NEXT_MATRIX: [(LoopType, [(usize, usize, bool); 3]); 4] = [
+        (LoopType::None, [(0, 1, true), (1, 2, true), (2, 2, false)]),
+        (
+            LoopType::NoAutostart,
+            [(0, 1, false), (1, 2, false), (2, 2, false)],
+        ),
+        (LoopType::Music, [(0, 0, true), (1, 1, true), (2, 2, true)]),
+        (LoopType::Queue, [(0, 1, true), (1, 2, true), (2, 0, true)]),
+    ];
+
+    #[test]
+    fn next_follows_the_loop_matrix() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for (loop_type, cases) in NEXT_MATRIX {
+            for (current, next, played) in cases {
+                assert_eq!(
+                    next_index(&loop_type, current, 3, false, &mut rng),
+                    (next, played),
+                    "{} from {}",
+                    loop_type,
+                    current
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn next_random_is_seeded_and_in_range() {
+        let indexes = |seed| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            (0..16)
+                .map(|current| next_index(&LoopType::Random, current % 3, 3, false, &mut rng))
+                .collect::<Vec<_>>()
+        };
+
+        let first = indexes(7);
+        assert_eq!(first, indexes(7));
+        assert!(first.iter().all(|&(index, played)| index < 3 && played));
+    }
+
+    #[test]
+    fn next_in_single_track_queue() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        for loop_type in LoopType::ALL {
+            let (index, _) = next_index(&loop_type, 0, 1, false, &mut rng);
+            assert_eq!(index, 0, "{}", loop_type);
+        }
+    }
+
+    #[test]
+    fn music_loop_skips_a_failed_track() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        assert_eq!(
+            next_index(&LoopType::Music, 1, 3, true, &mut rng),
+            (2, true)
+        );
+        assert_eq!(
+            next_index(&LoopType::Music, 2, 3, true, &mut rng),
+            (0, true)
+        );
+    }
+
+    #[test]
+    fn explicit_actions_resume_the_automatic_pause() {
+        let (paused, auto_paused) = (AtomicBool::new(true), AtomicBool::new(true));
+
+        assert!(resume_auto_paused(&paused, &auto_paused));
+        assert!(!paused.load(Ordering::Relaxed));
+        assert!(!auto_paused.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn explicit_actions_keep_the_user_pause() {
+        let (paused, auto_paused) = (AtomicBool::new(true), AtomicBool::new(false));
+
+        assert!(!resume_auto_paused(&paused, &auto_paused));
+        assert!(paused.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn failing_queue_stops() {
+        let mut rng = StdRng::seed_from_u64(1);
+
+        // Every track fails, so the music loop stops after trying each one once.
+        let (mut current, mut played) = (0, 1);
+        for failures in 1.. {
+            let (index, play) = next_index(&LoopType::Music, current, 3, true, &mut rng);
+            if !play || too_many_failures(failures, 3) {
+                break;
+            }
+
+            current = index;
+            played += 1;
+        }
+        assert_eq!(played, 3);
+
+        assert!(!too_many_failures(0, 1));
+        assert!(!too_many_failures(HYDROGEN_LOAD_FAILURE_LIMIT - 1, 100));
+        assert!(too_many_failures(HYDROGEN_LOAD_FAILURE_LIMIT, 100));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn music_serde_round_trip() {
+        use serenity::model::prelude::UserId;
+
+        use super::HydrogenMusic;
+
+        let music = HydrogenMusic {
+            encoded_track: "QAAAjQIAJVJpY2sgQXN0bGV5".to_owned(),
+            identifier: "dQw4w9WgXcQ".to_owned(),
+            length: 212000,
+            author: "Rick Astley".to_owned(),
+            title: "Never Gonna Give You Up".to_owned(),
+            uri: Some("https://www.youtube.com/watch?v=dQw4w9WgXcQ".to_owned()),
+            source: Some("youtube".to_owned()),
+            artwork: None,
+            requester_id: UserId::new(1),
+        };
+
+        let json = serde_json::to_string(&music).unwrap();
+        let decoded: HydrogenMusic = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(decoded.encoded_track, music.encoded_track);
+        assert_eq!(decoded.identifier, music.identifier);
+        assert_eq!(decoded.length, music.length);
+        assert_eq!(decoded.title, music.title);
+        assert_eq!(decoded.uri, music.uri);
+        assert_eq!(decoded.requester_id, music.requester_id);
+    }
 }
@@ -22,6 +22,9 @@ pub enum Error {
 
     /// The number of repetitions is invalid.
     Repetition(u8, RangeInclusive<u8>),
+
+    /// The modifier divides by zero.
+    DivideByZero,
 }
 
 impl Display for Error {
@@ -40,6 +43,7 @@ impl Display for Error {
                     repeat, range
                 )
             }
+            Error::DivideByZero => write!(f, "Invalid modifier: division by zero"),
         }
     }
 }
@@ -76,13 +80,16 @@ pub enum Modifier {
 }
 
 impl Modifier {
-    /// Applies the modifier to the given value.
+    /// Applies the modifier to the given value, saturating on overflow.
+    ///
+    /// Dividing by zero keeps the value unchanged, these modifiers are rejected by [Params::validate].
     pub fn apply(&self, value: i32) -> i32 {
         match self {
-            Self::Add(v) => value + v,
-            Self::Subtract(v) => value - v,
-            Self::Multiply(v) => value * v,
-            Self::Divide(v) => value / v,
+            Self::Add(v) => value.saturating_add(*v),
+            Self::Subtract(v) => value.saturating_sub(*v),
+            Self::Multiply(v) => value.saturating_mul(*v),
+            Self::Divide(0) => value,
+            Self::Divide(v) => value.saturating_div(*v),
         }
     }
 
@@ -146,6 +153,11 @@ impl Params {
             return Err(Error::Repetition(self.repeat, repetition_range));
         }
 
+        // Check for the division by zero.
+        if self.modifier == Modifier::Divide(0) {
+            return Err(Error::DivideByZero);
+        }
+
         Ok(())
     }
 
@@ -183,7 +195,7 @@ impl Params {
             rolls.push(roll);
         }
 
-        Ok(Roll(rolls, self.modifier))
+        Ok(Roll(rolls, self.modifier, self.dice_type))
     }
 }
 
@@ -221,9 +233,128 @@ impl ToString for Dice {
     }
 }
 
+/// How the results of a roll are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Every dice and the total of each repetition, see [Roll::to_string].
+    #[default]
+    Detailed,
+
+    /// Only the total of each repetition, see [Roll::to_sum_string].
+    Sum,
+
+    /// The dice notation and the total of each repetition, see [Roll::to_compact_string].
+    Compact,
+}
+
+impl Format {
+    /// Gets the format from its name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "detailed" => Some(Self::Detailed),
+            "sum" => Some(Self::Sum),
+            "compact" => Some(Self::Compact),
+            _ => None,
+        }
+    }
+
+    /// Gets the name of the format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Detailed => "detailed",
+            Self::Sum => "sum",
+            Self::Compact => "compact",
+        }
+    }
+}
+
 /// Results of a roll.
 #[derive(Debug, Clone)]
-pub struct Roll(Vec<Vec<Dice>>, Modifier);
+pub struct Roll(Vec<Vec<Dice>>, Modifier, DiceType);
+
+impl Roll {
+    /// Gets the total of each repetition with the modifier applied.
+    fn totals(&self) -> impl Iterator<Item = i32> + '_ {
+        self.0
+            .iter()
+            .map(|roll| self.1.apply(roll.iter().cloned().map(i32::from).sum()))
+    }
+
+    /// Converts the results to a string with only the total of each repetition.
+    pub fn to_sum_string(&self) -> String {
+        self.totals().map(|total| format!("{}\n", total)).collect()
+    }
+
+    /// Converts the results to a string with the dice notation (`NdX = total`) of each repetition.
+    pub fn to_compact_string(&self) -> String {
+        // Get the dice notation, including the modifier if it changes the result.
+        let sides = match self.2 {
+            DiceType::Fate => "F".to_owned(),
+            DiceType::Sided(sides) => sides.to_string(),
+        };
+        let modifier = match self.1 {
+            Modifier::Add(0) | Modifier::Subtract(0) => String::new(),
+            Modifier::Add(v) => format!("+{}", v),
+            Modifier::Subtract(v) => format!("-{}", v),
+            Modifier::Multiply(v) => format!("*{}", v),
+            Modifier::Divide(v) => format!("/{}", v),
+        };
+
+        self.0
+            .iter()
+            .zip(self.totals())
+            .map(|(roll, total)| format!("{}d{}{} = {}\n", roll.len(), sides, modifier, total))
+            .collect()
+    }
+
+    /// Converts the results to a string using the given format.
+    pub fn to_format_string(&self, format: Format) -> String {
+        match format {
+            Format::Detailed => self.to_string(),
+            Format::Sum => self.to_sum_string(),
+            Format::Compact => self.to_compact_string(),
+        }
+    }
+
+    /// Converts the results to a string using the given format, keeping it within `limit` characters.
+    ///
+    /// The repetitions that don't fit are replaced by the note returned by `note`, which receives how many were omitted.
+    pub fn to_limited_string(
+        &self,
+        format: Format,
+        limit: usize,
+        note: impl Fn(usize) -> String,
+    ) -> String {
+        truncate_lines(&self.to_format_string(format), limit, note)
+    }
+}
+
+/// Keeps the whole lines of the text that fit within `limit` characters, ending with the note about the omitted ones.
+fn truncate_lines(text: &str, limit: usize, note: impl Fn(usize) -> String) -> String {
+    if text.chars().count() <= limit {
+        return text.to_owned();
+    }
+
+    let lines = text.lines().collect::<Vec<_>>();
+    let mut result = String::new();
+    let mut length = 0;
+
+    for (kept, line) in lines.iter().enumerate() {
+        // The note must still fit after adding the line.
+        let line_length = line.chars().count() + 1;
+        let note_length = note(lines.len() - kept - 1).chars().count();
+        if length + line_length + note_length > limit {
+            result.push_str(&note(lines.len() - kept));
+            return result;
+        }
+
+        result.push_str(line);
+        result.push('\n');
+        length += line_length;
+    }
+
+    result
+}
 
 impl ToString for Roll {
     fn to_string(&self) -> String {
@@ -295,3 +426,77 @@ impl From<FateDice> for i8 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roll() -> Roll {
+        Roll(
+            vec![
+                vec![Dice::Sided(1), Dice::Sided(6)],
+                vec![Dice::Sided(3), Dice::Sided(4)],
+            ],
+            Modifier::Add(2),
+            DiceType::Sided(6),
+        )
+    }
+
+    #[test]
+    fn sum_format() {
+        assert_eq!(roll().to_sum_string(), "9\n9\n");
+    }
+
+    #[test]
+    fn compact_format() {
+        assert_eq!(roll().to_compact_string(), "2d6+2 = 9\n2d6+2 = 9\n");
+
+        let fate = Roll(
+            vec![vec![Dice::Fate(FateDice::Plus), Dice::Fate(FateDice::Zero)]],
+            Modifier::Add(0),
+            DiceType::Fate,
+        );
+        assert_eq!(fate.to_compact_string(), "2dF = 1\n");
+    }
+
+    #[test]
+    fn oversized_roll_is_truncated() {
+        let params = Params::new(50, DiceType::Sided(100), Modifier::Add(0), 50);
+        let result = params.roll().unwrap();
+        assert!(result.to_string().chars().count() > 4096);
+
+        for limit in [2000, 4096] {
+            let text = result.to_limited_string(Format::Detailed, limit, |count| {
+                format!("...and {} more", count)
+            });
+
+            assert!(text.chars().count() <= limit);
+            assert!(text.ends_with(" more"));
+            assert!(text.lines().filter(|line| line.starts_with('[')).count() < 50);
+        }
+    }
+
+    #[test]
+    fn small_roll_is_kept() {
+        let text = roll().to_limited_string(Format::Sum, 4096, |_| unreachable!());
+        assert_eq!(text, "9\n9\n");
+    }
+
+    #[test]
+    fn modifier_overflow() {
+        assert_eq!(Modifier::Multiply(i32::MAX).apply(5000), i32::MAX);
+        assert_eq!(Modifier::Subtract(i32::MAX).apply(-50), i32::MIN);
+        assert_eq!(Modifier::Divide(-1).apply(i32::MIN), i32::MAX);
+    }
+
+    #[test]
+    fn divide_by_zero() {
+        assert_eq!(Modifier::Divide(0).apply(10), 10);
+
+        let params = Params {
+            modifier: Modifier::Divide(0),
+            ..Default::default()
+        };
+        assert!(matches!(params.roll(), Err(Error::DivideByZero)));
+    }
+}
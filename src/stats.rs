@@ -0,0 +1,80 @@
+//! Hydrogen // Stats
+//!
+//! Usage statistics of the commands and components, kept only in memory and never reported anywhere.
+
+#[cfg(feature = "health")]
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use dashmap::DashMap;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+/// Usage of a single command or component.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Usage {
+    /// How many times it was executed.
+    pub count: u64,
+
+    /// Sum of the execution times in milliseconds.
+    pub total_ms: u64,
+
+    /// Longest execution time in milliseconds.
+    pub max_ms: u64,
+}
+
+/// Usage of each command and component, keyed by the command name or the component ID.
+#[derive(Default)]
+pub struct UsageStats {
+    entries: DashMap<String, Usage>,
+}
+
+impl UsageStats {
+    /// Records an execution, allocating the key only the first time it's used.
+    pub fn record(&self, name: &str, elapsed: Duration) {
+        let elapsed = elapsed.as_millis() as u64;
+
+        let update = |usage: &mut Usage| {
+            usage.count += 1;
+            usage.total_ms = usage.total_ms.saturating_add(elapsed);
+            usage.max_ms = usage.max_ms.max(elapsed);
+        };
+
+        match self.entries.get_mut(name) {
+            Some(mut usage) => update(&mut usage),
+            None => update(&mut self.entries.entry(name.to_owned()).or_default()),
+        }
+    }
+
+    /// Gets the usage of all commands and components, sorted by name.
+    #[cfg(feature = "health")]
+    pub fn snapshot(&self) -> BTreeMap<String, Usage> {
+        self.entries
+            .iter()
+            .map(|entry| (entry.key().clone(), *entry.value()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_accumulates() {
+        let stats = UsageStats::default();
+
+        stats.record("play", Duration::from_millis(10));
+        stats.record("play", Duration::from_millis(30));
+
+        assert_eq!(
+            stats.entries.get("play").map(|v| *v),
+            Some(Usage {
+                count: 2,
+                total_ms: 40,
+                max_ms: 30,
+            })
+        );
+    }
+}
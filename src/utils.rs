@@ -2,17 +2,25 @@
 //!
 //! Utility functions for Hydrogen's commands and components.
 
-use std::sync::Arc;
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
 use hydrogen_i18n::I18n;
 use serenity::{
     all::{ChannelId, CommandInteraction, Guild, GuildId, UserId},
+    builder::{CreateCommand, CreateCommandOption},
     client::Context,
 };
-use songbird::Songbird;
+use songbird::{Call, Songbird};
+use tokio::{sync::Mutex, time::sleep};
 use tracing::{error, warn};
 
-use crate::{manager::HydrogenManager, HydrogenContext, HYDROGEN_BUG_URL};
+use crate::{
+    manager::HydrogenManager, player::HydrogenMusic, HydrogenContext, HYDROGEN_BUG_URL,
+    HYDROGEN_LOGO_URL, HYDROGEN_VOICE_CONNECTION_INTERVAL, HYDROGEN_VOICE_CONNECTION_TIMEOUT,
+};
 
 /// Common data used by music commands and components.
 pub struct MusicCommonData {
@@ -70,6 +78,22 @@ impl MusicCommonData {
     }
 }
 
+/// Waits for Songbird to populate the voice connection info, returning `false` on timeout.
+pub async fn wait_for_connection(call: &Mutex<Call>) -> bool {
+    let timeout = Duration::from_millis(HYDROGEN_VOICE_CONNECTION_TIMEOUT);
+    let timer = Instant::now();
+
+    while call.lock().await.current_connection().is_none() {
+        if timer.elapsed() >= timeout {
+            return false;
+        }
+
+        sleep(Duration::from_millis(HYDROGEN_VOICE_CONNECTION_INTERVAL)).await;
+    }
+
+    true
+}
+
 /// Creates an error embed's description.
 pub fn error_message(i18n: &I18n, locale: &str, error: &str) -> String {
     format!(
@@ -80,6 +104,44 @@ pub fn error_message(i18n: &I18n, locale: &str, error: &str) -> String {
     )
 }
 
+/// Translates the command's name and description.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn translate_command(
+    i18n: Option<&I18n>,
+    category: &str,
+    name_key: &str,
+    description_key: &str,
+    command: CreateCommand,
+) -> CreateCommand {
+    match i18n {
+        Some(i18n) => {
+            let command = i18n.serenity_command_name(category, name_key, command);
+            i18n.serenity_command_description(category, description_key, command)
+        }
+        None => command,
+    }
+}
+
+/// Translates the command option's name and description.
+///
+/// If `i18n` is `None`, the translation will be ignored.
+pub fn translate_option(
+    i18n: Option<&I18n>,
+    category: &str,
+    name_key: &str,
+    description_key: &str,
+    option: CreateCommandOption,
+) -> CreateCommandOption {
+    match i18n {
+        Some(i18n) => {
+            let option = i18n.serenity_command_option_name(category, name_key, option);
+            i18n.serenity_command_option_description(category, description_key, option)
+        }
+        None => option,
+    }
+}
+
 /// Gets a string option from a command.
 pub fn get_str_option(command: &CommandInteraction, index: usize) -> Option<&str> {
     command.data.options.get(index)?.value.as_str()
@@ -106,10 +168,15 @@ pub fn time_to_string(seconds: i32) -> String {
     )
 }
 
-/// Creates a progress bar.
-pub fn progress_bar(current: i32, total: i32) -> String {
+/// Gets the artwork shown in the embeds of a track, falling back to Hydrogen's logo.
+pub fn artwork_url(music: &HydrogenMusic) -> &str {
+    music.artwork.as_deref().unwrap_or(HYDROGEN_LOGO_URL)
+}
+
+/// Creates a progress bar from the ratio of the track played.
+pub fn progress_bar(ratio: f32) -> String {
     let item_total = 30usize;
-    let item_count = (current as f32 / (total as f32 / item_total as f32)).round();
+    let item_count = (ratio.clamp(0.0, 1.0) * item_total as f32).round();
     let bar = "▓".repeat(item_count as usize);
     format!("╣{:░<width$.width$}╠", bar, width = item_total)
 }